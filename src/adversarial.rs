@@ -0,0 +1,117 @@
+//! Adversarial ("Absurdle") sessions, where the secret is only fixed once the guesses force it.
+use std::collections::HashMap;
+
+use crate::{WordleGame, LetterValidity, GameResult, GuessResult, check_guess, score_guess, solver::pattern_code};
+
+/// A Wordle game where every guess gets the feedback that keeps the most answers possible.
+pub struct AdversarialSession {
+    /// Game settings. `game.word` is kept as one of the answers still consistent with the board.
+    pub game: WordleGame,
+    candidates: Vec<String>,
+    guesses: Vec<(String, Vec<LetterValidity>)>,
+}
+
+impl AdversarialSession {
+    /// Create an `AdversarialSession` in starting state, where any word of the answer list may be the secret.
+    pub fn new(game: &WordleGame) -> AdversarialSession {
+        AdversarialSession {
+            game: game.clone(),
+            candidates: game.answer_list().to_vec(),
+            guesses: Vec::new(),
+        }
+    }
+
+    /// Makes a guess using `word`. If the guess is valid, score it to keep as many answers as possible.
+    /// Ties between feedback patterns go to the one revealing the least.
+    pub fn guess(&mut self, word: &str) -> Result<GameResult, GuessResult> {
+        if let Some(r) = check_guess(&self.game, &self.guesses, word) {
+            return Err(r);
+        }
+
+        let mut buckets: HashMap<u32, Vec<String>> = HashMap::new();
+        for w in self.candidates.drain(..) {
+            buckets.entry(pattern_code(&score_guess(&w, word))).or_default().push(w);
+        }
+        let (_, bucket) = buckets.into_iter()
+            .max_by(|(a, x), (b, y)| x.len().cmp(&y.len()).then(b.cmp(a)))
+            .ok_or(GuessResult::Invalid)?;
+        self.candidates = bucket;
+        self.game.word = self.candidates[0].clone();
+
+        let feedback = score_guess(&self.game.word, word);
+        let won = feedback.iter().all(|lv| *lv == LetterValidity::Correct);
+        self.guesses.push((word.to_string(), feedback));
+        if won {
+            Ok(GameResult::Win(self.guesses.len()))
+        } else if self.guesses.len() == self.game.max_guesses_usize() {
+            Ok(GameResult::OutOfGuesses)
+        } else {
+            Ok(GameResult::Cont)
+        }
+    }
+
+    pub fn get_guesses(&self) -> &Vec<(String, Vec<LetterValidity>)> {
+        &self.guesses
+    }
+
+    /// Get the number of answers still consistent with the board.
+    pub fn candidate_count(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Get the answers still consistent with the board, for debugging.
+    #[cfg(any(test, feature = "adversarial-debug"))]
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    /// Check whether an answer consistent with the board remains and there are guesses left to find it.
+    pub fn is_winnable(&self) -> bool {
+        let won = self.guesses.last().is_some_and(|(_, v)| v.iter().all(|lv| *lv == LetterValidity::Correct));
+        won || (!self.candidates.is_empty() && self.guesses.len() < self.game.max_guesses_usize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WordleGame, GameResult, GuessResult};
+
+    use super::AdversarialSession;
+
+    fn game(max_guesses: u32) -> WordleGame {
+        let words = ["bills", "fills", "hills", "mills", "pills", "spice"].iter().map(|w| w.to_string()).collect();
+        WordleGame::new("spice", words, max_guesses)
+    }
+
+    #[test]
+    fn guess1() {
+        let mut session = AdversarialSession::new(&game(6));
+        // Every "-ills" word stays possible, so "spice" can't be the answer
+        assert_eq!(session.guess("spice"), Ok(GameResult::Cont));
+        assert!(session.game.word.ends_with("ills"));
+        assert_eq!(session.guess("spice"), Err(GuessResult::AlreadyUsed));
+        assert_eq!(session.guess("xx"), Err(GuessResult::Invalid));
+    }
+
+    #[test]
+    fn candidate_count1() {
+        let mut session = AdversarialSession::new(&game(6));
+        assert_eq!(session.candidate_count(), 6);
+        assert_eq!(session.guess("spice"), Ok(GameResult::Cont));
+        // "pills" shares the yellow 'p' with "spice", leaving the other "-ills" words
+        assert_eq!(session.candidate_count(), 4);
+        assert_eq!(session.guess("bills"), Ok(GameResult::Cont));
+        assert_eq!(session.candidate_count(), 3);
+        assert_eq!(session.candidates(), ["fills", "hills", "mills"]);
+    }
+
+    #[test]
+    fn is_winnable1() {
+        let mut session = AdversarialSession::new(&game(2));
+        assert!(session.is_winnable());
+        assert_eq!(session.guess("bills"), Ok(GameResult::Cont));
+        assert!(session.is_winnable());
+        assert_eq!(session.guess("fills"), Ok(GameResult::OutOfGuesses));
+        assert!(!session.is_winnable());
+    }
+}
@@ -1,29 +1,537 @@
 /// CLI backend for Wordle.
-use std::{io::{self, Write, BufRead}, collections::HashMap, fmt};
+use std::{io::{self, Write, BufRead}, collections::HashMap, fs, thread, time::Duration};
 
-use termion::{color, style};
+use clap::{Args, ValueEnum};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use termion::{event::Key, input::TermRead, raw::IntoRawMode, style};
 
-use crate::{WordleSession, WordleGame, LetterValidity, GuessResult, GameResult};
+use crate::{WordleSession, WordleGame, LetterValidity, GuessResult, GameResult, solver::{self, Constraints}, tiles, tile_count, score_guess, repeated_grey_letters, theme::Theme, Progress, stats::GameStats};
 
-pub struct WordleSessionCLI<R, W> {
+/// How the game is written to the output.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Interactive colored board
+    #[default]
+    Text,
+
+    /// No board while playing; the finished game as a JSON document
+    Json,
+}
+
+/// Sets of messages shown when a game ends.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessagePack {
+    /// "You win!" and "Game over."
+    #[default]
+    Classic,
+
+    /// A random cheer or consolation
+    Cheerful,
+
+    /// A random pirate exclamation
+    Pirate,
+}
+
+impl MessagePack {
+    /// Choose a message for a won game.
+    pub fn win_message(&self, rng: &mut impl Rng) -> &'static str {
+        let messages: &[&'static str] = match self {
+            MessagePack::Classic => &["You win!"],
+            MessagePack::Cheerful => &["You win!", "Brilliant!", "Nailed it!", "Splendid!", "Well played!"],
+            MessagePack::Pirate => &["Treasure found!", "Shiver me timbers, ye got it!", "Yo ho ho, a win!"],
+        };
+        messages.choose(rng).copied().unwrap_or_default()
+    }
+
+    /// Choose a message for a game that ran out of guesses.
+    pub fn loss_message(&self, rng: &mut impl Rng) -> &'static str {
+        let messages: &[&'static str] = match self {
+            MessagePack::Classic => &["Game over."],
+            MessagePack::Cheerful => &["Game over.", "So close!", "Better luck next time!", "Tomorrow's another word!"],
+            MessagePack::Pirate => &["Ye walked the plank.", "Davy Jones takes this one.", "Arr, the word got away."],
+        };
+        messages.choose(rng).copied().unwrap_or_default()
+    }
+}
+
+/// Display options for the CLI backend.
+#[derive(Args, Debug, Clone, Default)]
+pub struct CliOptions {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Render rows right-to-left (for RTL-language word files)
+    #[arg(long)]
+    pub rtl: bool,
+
+    /// Point out guesses that are anagrams of the answer with every letter misplaced
+    #[arg(long)]
+    pub near_miss: bool,
+
+    /// Show the number of possible answers before each guess, unless `--lies` is set
+    #[arg(long)]
+    pub assist: bool,
+
+    /// Pin a letter to a position when counting possible answers, e.g. `--pin 1=a`
+    #[arg(long, value_parser = parse_pin)]
+    pub pin: Vec<(usize, char)>,
+
+    /// List up to this many possible answers under `--assist`
+    #[arg(long, value_name = "N")]
+    pub max_candidates: Option<usize>,
+
+    /// Delay between revealing each tile of a new guess, in milliseconds
+    #[arg(long, value_name = "MS")]
+    pub reveal_delay: Option<u64>,
+
+    /// Show how many answers the puzzle could have before the first guess
+    #[arg(long)]
+    pub show_pool_size: bool,
+
+    /// After each guess, say whether it matched more letters than the one before
+    #[arg(long)]
+    pub kids: bool,
+
+    /// Keep letters already found green in place, so only the other letters are typed, a key at a time
+    #[arg(long)]
+    pub lock_greens: bool,
+
+    /// Hide the board while playing and only show it once the game has ended
+    #[arg(long)]
+    pub blind: bool,
+
+    /// While playing, show only how many letters of each guess are green instead of the board
+    #[arg(long)]
+    pub assist_greens_count: bool,
+
+    /// Show a keyboard of the letters guessed so far
+    #[arg(long)]
+    pub keyboard: bool,
+
+    /// Keyboard rows separated by `/` [default: qwertyuiop/asdfghjkl/zxcvbnm]
+    #[arg(long)]
+    pub keyboard_layout: Option<String>,
+
+    /// Load board colors and the empty tile character from a TOML or JSON theme file
+    #[arg(long, value_name = "FILE", value_parser = Theme::load)]
+    pub theme: Option<Theme>,
+
+    /// After the game, show on which guess each position first turned green
+    #[arg(long)]
+    pub show_discovery: bool,
+
+    /// Messages shown when the game ends
+    #[arg(long, value_enum, default_value_t = MessagePack::Classic)]
+    pub message_pack: MessagePack,
+
+    /// Seed for choosing the end messages, to get the same ones every time
+    #[arg(long, value_name = "SEED")]
+    pub message_seed: Option<u64>,
+
+    /// After the game, show its score: points for new greens and yellows, penalties for reusing absent letters
+    #[arg(long)]
+    pub score: bool,
+
+    /// After running out of guesses, offer to play the same word again before it is revealed
+    #[arg(long)]
+    pub retry_same: bool,
+
+    /// Whether the output is an interactive terminal; animations are skipped otherwise
+    #[arg(skip)]
+    pub interactive: bool,
+}
+
+/// Keyboard layout used when none is configured.
+const QWERTY_LAYOUT: &str = "qwertyuiop/asdfghjkl/zxcvbnm";
+
+/// Clean up pasted input before it is guessed: zero-width characters are removed, 
+/// curly quotes become straight quotes, non-breaking spaces become spaces, and 
+/// surrounding whitespace is trimmed.
+fn normalize_input(input: &str) -> String {
+    input.chars()
+        .filter(|c| !matches!(c, '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'))
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201C}' | '\u{201D}' => '"',
+            '\u{00A0}' => ' ',
+            _ => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Parse a 1-based `POS=LETTER` pin into a 0-based position and letter.
+fn parse_pin(s: &str) -> Result<(usize, char), String> {
+    let (pos, letter) = s.split_once('=').ok_or("Expected POS=LETTER")?;
+    let pos: usize = pos.parse().map_err(|_| format!("Invalid position: {}", pos))?;
+    let mut letters = letter.chars();
+    match (pos, letters.next(), letters.next()) {
+        (1.., Some(c), None) => Ok((pos - 1, c)),
+        (0, _, _) => Err(String::from("Positions start at 1")),
+        _ => Err(format!("Expected a single letter: {}", letter)),
+    }
+}
+
+/// Build a row of `word_len` tiles from `greens` and the tiles `typed` for the other positions, in order.
+/// Open positions left without a typed tile are shown as `_`, and typed tiles beyond the open positions are dropped.
+fn fill_locked_greens(greens: &[(usize, String)], word_len: usize, typed: &[String]) -> String {
+    let mut typed = typed.iter();
+    (0..word_len)
+        .map(|i| match greens.iter().find(|(j, _)| *j == i) {
+            Some((_, t)) => t.as_str(),
+            None => typed.next().map_or("_", |t| t.as_str()),
+        })
+        .collect()
+}
+
+/// Read a guess key by key with the `greens` locked in place: typed letters fill the other tiles in order, 
+/// Backspace clears the last one, and Enter submits once every tile is filled. A `/` typed before any letter 
+/// starts a command instead, read up to Enter. Each change redraws the row on `echo`, if given.
+/// Returns `None` if the keys run out or Ctrl-C or Ctrl-D is pressed first.
+fn read_locked_guess(keys: impl Iterator<Item = io::Result<Key>>, greens: &[(usize, String)], word_len: usize, 
+    mut echo: Option<&mut dyn Write>) -> Result<Option<String>, io::Error> {
+    let open = word_len - greens.len();
+    let mut typed: Vec<String> = Vec::new();
+    let mut command: Option<String> = None;
+    for key in keys {
+        match (key?, command.as_mut()) {
+            (Key::Ctrl('c' | 'd'), _) => break,
+            (Key::Char('\n'), Some(c)) => return Ok(Some(c.clone())),
+            (Key::Char('\n'), None) if typed.len() == open => {
+                if let Some(w) = echo.as_mut() {
+                    write!(w, "\r\n")?;
+                }
+                return Ok(Some(fill_locked_greens(greens, word_len, &typed)));
+            },
+            (Key::Char('/'), None) if typed.is_empty() => command = Some(String::from("/")),
+            (Key::Char('\n'), None) => {},
+            (Key::Char(ch), Some(c)) => c.push(ch),
+            (Key::Char(ch), None) => {
+                // A combining mark joins the tile before it
+                let joins = typed.last().is_some_and(|last| tile_count(&format!("{}{}", last, ch)) == 1);
+                if let Some(last) = typed.last_mut().filter(|_| joins) {
+                    last.push(ch);
+                } else if typed.len() < open {
+                    typed.push(ch.to_string());
+                }
+            },
+            (Key::Backspace, Some(c)) => {
+                c.pop();
+                if c.is_empty() {
+                    command = None;
+                }
+            },
+            (Key::Backspace, None) => {
+                typed.pop();
+            },
+            _ => {},
+        }
+        if let Some(w) = echo.as_mut() {
+            let row = command.clone().unwrap_or_else(|| fill_locked_greens(greens, word_len, &typed));
+            write!(w, "\r{}{}", termion::clear::CurrentLine, row)?;
+            w.flush()?;
+        }
+    }
+    Ok(None)
+}
+
+/// Score each `SECRET GUESS` line of `reader`, writing one line to `writer` per input line:
+/// the pattern of the guess (see `LetterValidity::to_char`), or an error message. Stops at the end of input.
+pub fn score_stream(reader: impl BufRead, mut writer: impl Write) -> Result<(), io::Error> {
+    for line in reader.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [secret, guess] if tiles(secret).len() == tiles(guess).len() => {
+                let pattern: String = score_guess(secret, guess).iter().map(LetterValidity::to_char).collect();
+                writeln!(&mut writer, "{}", pattern)?;
+            },
+            [_, _] => writeln!(&mut writer, "error: secret and guess differ in length")?,
+            _ => writeln!(&mut writer, "error: expected SECRET GUESS")?,
+        }
+        // Answer each line right away for programs waiting on the pipe
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Color each letter of `word` by its validity using the escape sequences of `color_map`, ending with a style reset.
+pub fn render_row(word: &str, validity: &[LetterValidity], color_map: &HashMap<LetterValidity, String>) -> String {
+    let mut text: String = tiles(word).into_iter().zip(validity)
+        .map(|(c, lv)| format!("{}{}", color_map.get(lv).map_or("", String::as_str), c))
+        .collect();
+    text.push_str(style::Reset.as_ref());
+    text
+}
+
+/// Output backend used by `WordleSessionCLI` to draw the game.
+pub trait BoardRenderer {
+    /// Draw the board of previous guesses and remaining attempts.
+    fn render_board(&mut self, session: &WordleSession) -> Result<(), io::Error>;
+
+    /// Draw a single line of text, such as a prompt or a rejection message.
+    fn render_message(&mut self, message: &str) -> Result<(), io::Error>;
+
+    /// Draw the final state of the game once it has ended.
+    fn render_end(&mut self, session: &WordleSession, result: &GameResult) -> Result<(), io::Error>;
+}
+
+/// Formats a scored guess as a single line of the board.
+pub trait FeedbackFormatter {
+    /// Format `word` with its letter validity as one row.
+    fn format_row(&self, word: &str, validity: &[LetterValidity]) -> String;
+}
+
+/// Formats rows with terminal colors, the default `FeedbackFormatter`.
+pub struct ColorFormatter {
+    rtl: bool,
+    color_map: HashMap<LetterValidity, String>,
+}
+
+impl ColorFormatter {
+    /// Create a `ColorFormatter` using the colors of `theme`, reversing rows if `rtl` is set.
+    pub fn new(theme: &Theme, rtl: bool) -> ColorFormatter {
+        ColorFormatter {
+            rtl,
+            color_map: theme.colors.iter().map(|(lv, c)| (*lv, c.fg())).collect(),
+        }
+    }
+}
+
+impl FeedbackFormatter for ColorFormatter {
+    fn format_row(&self, word: &str, validity: &[LetterValidity]) -> String {
+        if !self.rtl {
+            return render_row(word, validity, &self.color_map);
+        }
+        // Letters keep their logical positions; only the visual order is reversed for RTL
+        let (mut word, mut validity): (Vec<&str>, Vec<LetterValidity>) = tiles(word).into_iter().zip(validity.iter().copied()).unzip();
+        word.reverse();
+        validity.reverse();
+        render_row(&word.concat(), &validity, &self.color_map)
+    }
+}
+
+/// Renders the game to a terminal using termion colors.
+pub struct TermionRenderer<W> {
+    writer: W,
+    formatter: Box<dyn FeedbackFormatter>,
+    rtl: bool,
+    blind: bool,
+    greens_count: bool,
+    keyboard: Option<Vec<Vec<char>>>,
+    reveal_delay: Option<Duration>,
+    revealed: usize,
+    messages: MessagePack,
+    rng: StdRng,
+    placeholder: char,
+    color_map: HashMap<LetterValidity, String>
+}
+
+impl<W: Write> TermionRenderer<W> {
+    /// Create a `TermionRenderer` writing to `writer`.
+    pub fn new(writer: W, options: &CliOptions) -> TermionRenderer<W> {
+        let theme = options.theme.clone().unwrap_or_default();
+        let formatter = Box::new(ColorFormatter::new(&theme, options.rtl));
+        TermionRenderer::with_formatter(writer, options, formatter)
+    }
+
+    /// Create a `TermionRenderer` writing to `writer`, formatting each guess with `formatter`.
+    pub fn with_formatter(writer: W, options: &CliOptions, formatter: Box<dyn FeedbackFormatter>) -> TermionRenderer<W> {
+        let theme = options.theme.clone().unwrap_or_default();
+        TermionRenderer {
+            writer,
+            formatter,
+            rtl: options.rtl,
+            blind: options.blind,
+            greens_count: options.assist_greens_count,
+            keyboard: options.keyboard.then(|| {
+                options.keyboard_layout.as_deref().unwrap_or(QWERTY_LAYOUT)
+                    .split('/')
+                    .map(|row| row.chars().collect())
+                    .collect()
+            }),
+            reveal_delay: options.reveal_delay
+                .filter(|_| options.interactive)
+                .map(Duration::from_millis),
+            revealed: 0,
+            messages: options.message_pack,
+            rng: options.message_seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            placeholder: theme.placeholder,
+            color_map: theme.colors.iter().map(|(lv, c)| (*lv, c.fg())).collect(),
+        }
+    }
+
+    /// Clear the terminal and draw `guesses` as the board, even in blind mode
+    fn reveal_board(&mut self, session: &WordleSession, guesses: &[(String, Vec<LetterValidity>)]) -> Result<(), io::Error> {
+        write!(&mut self.writer, "{}{}", termion::clear::All, termion::cursor::Goto(1, 1))?;
+        self.print_board(session, guesses)
+    }
+
+    /// Print the previous guesses
+    fn print_board(&mut self, session: &WordleSession, guesses: &[(String, Vec<LetterValidity>)]) -> Result<(), io::Error> {
+        for (i, (w, v)) in guesses.iter().enumerate() {
+            // Only animate rows that haven't been revealed yet, by redrawing the row one more tile at a time
+            if let Some(d) = self.reveal_delay.filter(|_| i >= self.revealed) {
+                let row = tiles(w);
+                for n in 1..row.len() {
+                    write!(&mut self.writer, "\r{}", self.formatter.format_row(&row[..n].concat(), &v[..n]))?;
+                    self.writer.flush()?;
+                    thread::sleep(d);
+                }
+                write!(&mut self.writer, "\r")?;
+            }
+            writeln!(&mut self.writer, "{}", self.formatter.format_row(w, v))?;
+        }
+        self.revealed = session.get_guesses().len();
+        // Print spaces for remaining attempts
+        for _ in session.get_guesses().len()..session.game.max_guesses_usize() {
+            writeln!(&mut self.writer, "{}", self.placeholder.to_string().repeat(session.game.word_len))?;
+        }   
+
+        if self.keyboard.is_some() {
+            writeln!(&mut self.writer)?;
+            self.print_keyboard(session)?;
+        }
+
+        Ok(())
+    }
+
+    /// Print the keyboard rows, coloring each letter by its best known validity
+    fn print_keyboard(&mut self, session: &WordleSession) -> Result<(), io::Error> {
+        let states = session.letter_states();
+        for row in self.keyboard.iter().flatten() {
+            let mut row: Vec<char> = row.clone();
+            if self.rtl {
+                row.reverse();
+            }
+            for c in row {
+                match states.get(c.to_string().as_str()) {
+                    Some(lv) => write!(&mut self.writer, "{}{}", self.color_map.get(lv).unwrap(), c)?,
+                    None => write!(&mut self.writer, "{}{}", style::Reset, c)?,
+                }
+            }
+            writeln!(&mut self.writer, "{}", style::Reset)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> BoardRenderer for TermionRenderer<W> {
+    /// Clear the terminal and draw the board, unless it is hidden or reduced to green counts until the end
+    fn render_board(&mut self, session: &WordleSession) -> Result<(), io::Error> {
+        if !self.blind && !self.greens_count {
+            return self.reveal_board(session, session.get_guesses());
+        }
+        write!(&mut self.writer, "{}{}", termion::clear::All, termion::cursor::Goto(1, 1))?;
+        if self.greens_count && !self.blind {
+            for (w, v) in session.get_guesses().iter() {
+                let greens = v.iter().filter(|lv| **lv == LetterValidity::Correct).count();
+                writeln!(&mut self.writer, "{}  Greens: {}", w, greens)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_message(&mut self, message: &str) -> Result<(), io::Error> {
+        writeln!(&mut self.writer, "{}", message)
+    }
+
+    /// Draw end result, with the true colors of any tiles that were lied about
+    fn render_end(&mut self, session: &WordleSession, result: &GameResult) -> Result<(), io::Error> {
+        let honest = session.honest_guesses();
+        match result {
+            GameResult::OutOfGuesses => {
+                self.reveal_board(session, &honest)?;
+                let message = self.messages.loss_message(&mut self.rng);
+                writeln!(&mut self.writer, "{}", message)?;
+                writeln!(&mut self.writer, "Answer: {}", session.game.word)?;
+                Ok(())
+            },
+            GameResult::Win(_) => {
+                self.reveal_board(session, &honest)?;
+                let message = self.messages.win_message(&mut self.rng);
+                writeln!(&mut self.writer, "{}", message)?;
+                // With hidden answers the secret is deduced rather than typed
+                if session.guessed_words().last() != Some(&session.game.word.as_str()) {
+                    writeln!(&mut self.writer, "Answer: {}", session.game.word)?;
+                }
+                Ok(())
+            },
+            GameResult::GaveUp => {
+                self.reveal_board(session, &honest)?;
+                writeln!(&mut self.writer, "You gave up.")?;
+                writeln!(&mut self.writer, "Answer: {}", session.game.word)?;
+                Ok(())
+            },
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid result"))
+        }
+    }
+}
+
+/// Draws nothing while playing, then writes the finished game as JSON for scripts.
+pub struct JsonRenderer<W> {
+    writer: W,
+}
+
+impl<W: Write> JsonRenderer<W> {
+    /// Create a `JsonRenderer` writing to `writer`.
+    pub fn new(writer: W) -> JsonRenderer<W> {
+        JsonRenderer { writer }
+    }
+}
+
+impl<W: Write> BoardRenderer for JsonRenderer<W> {
+    fn render_board(&mut self, _session: &WordleSession) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn render_message(&mut self, _message: &str) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn render_end(&mut self, session: &WordleSession, _result: &GameResult) -> Result<(), io::Error> {
+        writeln!(&mut self.writer, "{}", GameStats::from_session(session).to_json())
+    }
+}
+
+pub struct WordleSessionCLI<R, D> {
     session: WordleSession,
     reader: R,
-    writer: W,
-    color_map: HashMap<LetterValidity, Box<dyn fmt::Display>>
+    renderer: D,
+    options: CliOptions,
+    notice: Option<String>,
 }
 
-impl<R: BufRead, W: Write> WordleSessionCLI<R, W> {
+impl<R: BufRead, W: Write> WordleSessionCLI<R, TermionRenderer<W>> {
     /// Create a `WordleSessionCLI` in starting state.
-    pub fn new(game: &WordleGame, reader: R, writer: W) -> WordleSessionCLI<R, W> {
+    pub fn new(game: &WordleGame, reader: R, writer: W) -> WordleSessionCLI<R, TermionRenderer<W>> {
+        WordleSessionCLI::with_options(game, reader, writer, CliOptions::default())
+    }
+
+    /// Create a `WordleSessionCLI` in starting state using the given display options.
+    pub fn with_options(game: &WordleGame, reader: R, writer: W, options: CliOptions) -> WordleSessionCLI<R, TermionRenderer<W>> {
+        let renderer = TermionRenderer::new(writer, &options);
+        WordleSessionCLI::with_renderer(game, reader, renderer, options)
+    }
+
+    /// Print the previous guesses
+    #[cfg(test)]
+    fn print_board(&mut self) -> Result<(), io::Error> {
+        self.renderer.print_board(&self.session, self.session.get_guesses())
+    }
+}
+
+impl<R: BufRead, D: BoardRenderer> WordleSessionCLI<R, D> {
+    /// Create a `WordleSessionCLI` in starting state drawing through `renderer`.
+    pub fn with_renderer(game: &WordleGame, reader: R, renderer: D, options: CliOptions) -> WordleSessionCLI<R, D> {
         WordleSessionCLI { 
-            session: WordleSession::new(&game),
-            reader: reader,
-            writer: writer,
-            color_map: HashMap::from([
-                (LetterValidity::Correct, Box::new(color::Fg(color::LightGreen)) as Box<dyn fmt::Display>),
-                (LetterValidity::Incorrect, Box::new(color::Fg(color::LightWhite))),
-                (LetterValidity::WrongPos, Box::new(color::Fg(color::LightYellow))),
-            ]),
+            session: WordleSession::new(game),
+            reader,
+            renderer,
+            options,
+            notice: None,
         }   
     }
 
@@ -33,12 +541,27 @@ impl<R: BufRead, W: Write> WordleSessionCLI<R, W> {
         loop {
             self.run_loop(&mut result)?;
             match &result {
-                Ok(r) => match r {
-                    GameResult::Cont => continue,
-                    GameResult::OutOfGuesses | GameResult::Win => {
-                        self.end_game(r)?;
-                        break
+                Ok(GameResult::Cont) => continue,
+                Ok(GameResult::OutOfGuesses) if self.options.retry_same && self.ask_retry()? => {
+                    // Same game, so the secret and word list are kept exactly
+                    self.session = WordleSession::new(&self.session.game);
+                    result = Ok(GameResult::Cont);
+                },
+                Ok(r @ (GameResult::OutOfGuesses | GameResult::Win(_) | GameResult::GaveUp)) => {
+                    self.renderer.render_end(&self.session, r)?;
+                    if self.options.show_discovery {
+                        let found: Vec<String> = self.session.discovery_map().iter()
+                            .map(|n| n.map_or(String::from("-"), |n| n.to_string()))
+                            .collect();
+                        self.renderer.render_message(&format!("Found on guess: {}", found.join(" ")))?;
                     }
+                    if self.session.game.max_lies > 0 {
+                        self.reveal_lies()?;
+                    }
+                    if self.options.score {
+                        self.renderer.render_message(&format!("Score: {}", self.session.score()))?;
+                    }
+                    break
                 },
                 _ => continue,
             }
@@ -47,90 +570,183 @@ impl<R: BufRead, W: Write> WordleSessionCLI<R, W> {
         Ok(())
     }
 
-    /// Clear the terminal and draw the board
-    fn draw_head(&mut self) -> Result<(), io::Error> {
-        write!(&mut self.writer, "{}{}", termion::clear::All, termion::cursor::Goto(1, 1))?;
-        self.print_board()?;
-        Ok(())
-    }
-
     /// Draw the game, then receive input from the player for the current guess
     fn run_loop(&mut self, prev_result: &mut Result<GameResult, GuessResult>) -> Result<(), io::Error> {
-        self.draw_head()?;
-        match prev_result {
-            Ok(_) => writeln!(&mut self.writer)?,
-            Err(r) => match r {
-                GuessResult::AlreadyUsed => writeln!(&mut self.writer, "You've already used that word!")?,
-                GuessResult::Invalid => writeln!(&mut self.writer, "Invalid word.")?,
-                GuessResult::NotInDict => writeln!(&mut self.writer, "That word doesn't exist.")?,
-                _ => writeln!(&mut self.writer)?,
+        self.renderer.render_board(&self.session)?;
+        let notice = self.notice.take();
+        let message = match (&notice, &*prev_result) {
+            (Some(n), _) => n.as_str(),
+            (None, Ok(_)) if self.options.near_miss && self.last_guess_near_miss() => 
+                "So close — right letters, all misplaced!",
+            (None, Ok(_)) => "",
+            (None, Err(r)) => match r {
+                GuessResult::AlreadyUsed => "You've already used that word!",
+                GuessResult::Invalid => "Invalid word.",
+                GuessResult::NotInDict => "That word doesn't exist.",
+                GuessResult::IgnoresHints => "Hard mode: your guess must use the hints revealed so far.",
+                _ => "",
             },
+        };
+        self.renderer.render_message(message)?;
+        if self.options.kids && self.session.guesses.len() > 1 && prev_result.is_ok() {
+            let (w, _) = self.session.guesses.last().unwrap();
+            self.renderer.render_message(match self.session.relative_progress(w) {
+                Progress::Warmer => "Warmer!",
+                Progress::Colder => "Colder!",
+                Progress::Same => "Same as before.",
+            })?;
+        }
+        if self.options.show_pool_size && self.session.guesses.is_empty() {
+            let pool_size = self.session.game.answer_list().len();
+            self.renderer.render_message(&format!("This puzzle has {} possible answers", pool_size))?;
+        }
+        // The count would come from a board that may be lying, and could even rule out the secret
+        if self.options.assist && self.session.game.max_lies == 0 {
+            let constraints = Constraints { greens: self.options.pin.clone(), ..Default::default() };
+            let candidates = solver::filter_candidates_with_feedback(self.session.game.answer_list(), self.session.get_guesses(), 
+                self.session.game.feedback, &constraints, self.options.max_candidates);
+            self.renderer.render_message(&format!("Possible answers: {}", candidates.total))?;
+            if self.options.max_candidates.is_some() {
+                let more = if candidates.has_more() { " …" } else { "" };
+                self.renderer.render_message(&format!("{}{}", candidates.words.join(" "), more))?;
+            }
+            if let Some((w, v)) = self.session.guesses.last() {
+                for c in repeated_grey_letters(w, v) {
+                    self.renderer.render_message(&format!(
+                        "Note: grey '{}' means the word has no more copies of it, not that it is absent.", c))?;
+                }
+                for i in self.session.wasted_placements(w) {
+                    let c = tiles(w).get(i).copied().unwrap_or_default();
+                    self.renderer.render_message(&format!(
+                        "Warning: '{}' was already ruled out at position {}.", c, i + 1))?;
+                }
+            }
+        }
+        let greens = if self.options.lock_greens { self.session.green_positions() } else { Vec::new() };
+        if greens.is_empty() {
+            self.renderer.render_message("Enter your word:")?;
+        } else {
+            let template = fill_locked_greens(&greens, self.session.game.word_len, &[]);
+            self.renderer.render_message(&format!("Enter the missing letters: {}", template))?;
         }
-        writeln!(&mut self.writer, "Enter your word:")?;
-        
-        let mut input = String::new();
-        self.reader.read_line(&mut input)?;
-        let input = input.trim().to_string();
         
+        let input = if greens.is_empty() {
+            let mut input = String::new();
+            if self.reader.read_line(&mut input)? == 0 {
+                None
+            } else {
+                Some(normalize_input(&input))
+            }
+        } else if self.options.interactive {
+            // Raw mode lets the row be edited a key at a time, and is left again when `stdout` is dropped
+            let mut stdout = io::stdout().into_raw_mode()?;
+            read_locked_guess((&mut self.reader).keys(), &greens, self.session.game.word_len, Some(&mut stdout))?.map(|input| normalize_input(&input))
+        } else {
+            read_locked_guess((&mut self.reader).keys(), &greens, self.session.game.word_len, None)?.map(|input| normalize_input(&input))
+        };
+        // The input ran out before the game ended, so end it as if the player gave up
+        let input = match input {
+            Some(input) => input,
+            None => {
+                *prev_result = Ok(self.session.give_up());
+                return Ok(());
+            },
+        };
+        if input == "/giveup" {
+            *prev_result = Ok(self.session.give_up());
+            return Ok(());
+        }
+        if let Some(command) = input.strip_prefix('/') {
+            self.notice = Some(self.run_command(command));
+            return Ok(());
+        }
         *prev_result = self.session.guess(&input);
         Ok(())
     }
 
-    /// Draw end result
-    fn end_game(&mut self, result: &GameResult) -> Result<(), io::Error> {
-        match result {
-            GameResult::OutOfGuesses => {
-                self.draw_head()?;
-                writeln!(&mut self.writer, "Game over.")?;
-                writeln!(&mut self.writer, "Answer: {}", self.session.game.word)?;
-                Ok(())
+    /// List the yellow letters that were shown as grey
+    fn reveal_lies(&mut self) -> Result<(), io::Error> {
+        if self.session.lies().is_empty() {
+            return self.renderer.render_message("No letters were hidden.");
+        }
+        for &(g, i) in self.session.lies() {
+            let (w, _) = &self.session.get_guesses()[g];
+            let letter = tiles(w).get(i).copied().unwrap_or_default();
+            self.renderer.render_message(&format!(
+                "Guess {}, letter {}: '{}' was shown grey but is in the word.", g + 1, i + 1, letter))?;
+        }
+        Ok(())
+    }
+
+    /// Show the final board and ask whether to play the same word again
+    fn ask_retry(&mut self) -> Result<bool, io::Error> {
+        self.renderer.render_board(&self.session)?;
+        self.renderer.render_message("Out of guesses. Try the same word again? (y/n)")?;
+        let mut input = String::new();
+        self.reader.read_line(&mut input)?;
+        Ok(matches!(normalize_input(&input).to_lowercase().as_str(), "y" | "yes"))
+    }
+
+    /// Run an in-game command (the input without its leading `/`), returning the message to show
+    fn run_command(&mut self, command: &str) -> String {
+        let args: Vec<&str> = command.split_whitespace().collect();
+        match args.as_slice() {
+            ["mode", "assist"] => {
+                self.options.assist = true;
+                String::from("Assist mode on.")
             },
-            GameResult::Win => {
-                self.draw_head()?;
-                writeln!(&mut self.writer, "You win!")?;
-                Ok(())
+            ["mode", "hard"] => {
+                if self.session.set_hard_mode(true) {
+                    String::from("Hard mode on.")
+                } else {
+                    String::from("Can't turn on hard mode: earlier guesses don't use the revealed hints.")
+                }
             },
-            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid result"))
+            ["mode", "normal"] => {
+                self.options.assist = false;
+                self.session.set_hard_mode(false);
+                String::from("Normal mode on.")
+            },
+            ["mode", ..] => String::from("Usage: /mode assist|hard|normal"),
+            ["svg", path] => {
+                let svg = self.session.to_svg_with_theme(&self.options.theme.clone().unwrap_or_default());
+                match fs::write(path, svg) {
+                    Ok(()) => format!("Board saved to {}.", path),
+                    Err(e) => format!("Can't save board: {}", e),
+                }
+            },
+            ["svg", ..] => String::from("Usage: /svg FILE"),
+            _ => format!("Unknown command: /{}", command),
         }
     }
 
-    /// Print the previous guesses
-    fn print_board(&mut self) -> Result<(), io::Error> {
-        for (w, v) in self.session.guesses.iter() {
-            for (c, lv) in w.chars().into_iter().zip(v) {
-                write!(&mut self.writer, "{}{}", self.color_map.get(lv).unwrap(), c.to_string())?;
-            }
-            writeln!(&mut self.writer, "{}", style::Reset)?;
+    /// Check whether every letter of the last guess is in the word but in the wrong position
+    fn last_guess_near_miss(&self) -> bool {
+        match self.session.guesses.last() {
+            Some((_, v)) => v.iter().all(|lv| *lv == LetterValidity::WrongPos),
+            None => false,
         }
-        // Print spaces for remaining attempts
-        for _ in self.session.guesses.len()..(self.session.game.max_guesses as usize) {
-            writeln!(&mut self.writer, "{}", "·".repeat(self.session.game.word_len))?;
-        }   
-
-        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::io::Write;
+    use std::{io::Write, sync::Arc, time::Instant};
 
+    use clap::Parser;
+    use rand::{rngs::StdRng, SeedableRng};
     use termion::{color, style};
 
-    use crate::{WordleGame};
+    use crate::{WordleGame, WordleSession, WordList, GameResult, Config, LetterValidity, GuessRow, theme::Theme, tiles};
 
-    use super::WordleSessionCLI;
+    use super::{WordleSessionCLI, CliOptions, BoardRenderer, FeedbackFormatter, TermionRenderer, JsonRenderer, OutputFormat, 
+        MessagePack, render_row, parse_pin, normalize_input, score_stream, fill_locked_greens, read_locked_guess};
 
     #[test]
     fn print_board1() {
         let input = b"";
         let mut output = Vec::new();
-        let mut session = WordleSessionCLI::new(&WordleGame { 
-            word: String::from("apple"), 
-            word_list: vec![String::from("apple"), String::from("grape")], 
-            word_len: 5, 
-            max_guesses: 3,
-        }, input.as_slice(), &mut output);
+        let mut session = WordleSessionCLI::new(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 3), input.as_slice(), &mut output);
         session.print_board().expect("Failed to print to output");
         let mut expected_output = Vec::new();
         for _ in 0..3 {
@@ -144,12 +760,7 @@ mod tests {
     fn print_board2() {
         let input = b"";
         let mut output = Vec::new();
-        let mut session = WordleSessionCLI::new(&WordleGame { 
-            word: String::from("apple"), 
-            word_list: vec![String::from("apple"), String::from("grape")], 
-            word_len: 5, 
-            max_guesses: 2,
-        }, input.as_slice(), &mut output);
+        let mut session = WordleSessionCLI::new(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 2), input.as_slice(), &mut output);
         assert!(matches!(session.session.guess(&String::from("grape")), Result::Ok(_)));
         session.print_board().expect("Failed to print to output");
         let mut expected_output = Vec::new();
@@ -165,4 +776,605 @@ mod tests {
         assert_eq!(String::from_utf8(output).expect("Output not in UTF-8"), 
             String::from_utf8(expected_output).expect("Expected output not in UTF-8"));
     }
+
+    #[test]
+    fn print_board_rtl() {
+        let input = b"";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 1), input.as_slice(), &mut output, CliOptions { rtl: true, ..Default::default() });
+        assert!(matches!(session.session.guess(&String::from("grape")), Result::Ok(_)));
+        session.print_board().expect("Failed to print to output");
+        let mut expected_output = Vec::new();
+        writeln!(&mut expected_output, "{}e{}p{}a{}r{}g{}", 
+            color::Fg(color::LightGreen), 
+            color::Fg(color::LightYellow), 
+            color::Fg(color::LightYellow), 
+            color::Fg(color::LightWhite), 
+            color::Fg(color::LightWhite),
+            style::Reset).expect("Failed to write to expected output");
+        
+        assert_eq!(String::from_utf8(output).expect("Output not in UTF-8"), 
+            String::from_utf8(expected_output).expect("Expected output not in UTF-8"));
+    }
+
+    #[test]
+    fn render_row1() {
+        use LetterValidity::*;
+
+        let color_map = Theme::default().colors.iter().map(|(lv, c)| (*lv, c.fg())).collect();
+        assert_eq!(render_row("grape", &[Incorrect, Incorrect, WrongPos, WrongPos, Correct], &color_map), format!("{}g{}r{}a{}p{}e{}", 
+            color::Fg(color::LightWhite), 
+            color::Fg(color::LightWhite), 
+            color::Fg(color::LightYellow), 
+            color::Fg(color::LightYellow), 
+            color::Fg(color::LightGreen),
+            style::Reset));
+    }
+
+    #[test]
+    fn near_miss1() {
+        let input = b"pleap\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", vec![String::from("apple"), String::from("pleap")], 3), input.as_slice(), &mut output, CliOptions { near_miss: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert_eq!(output.matches("So close — right letters, all misplaced!").count(), 1);
+    }
+
+    /// Renderer recording each call instead of drawing.
+    struct MockRenderer {
+        calls: Vec<String>,
+    }
+
+    impl BoardRenderer for MockRenderer {
+        fn render_board(&mut self, session: &WordleSession) -> Result<(), std::io::Error> {
+            self.calls.push(format!("board {}", session.get_guesses().len()));
+            Ok(())
+        }
+
+        fn render_message(&mut self, message: &str) -> Result<(), std::io::Error> {
+            self.calls.push(format!("message {}", message));
+            Ok(())
+        }
+
+        fn render_end(&mut self, _session: &WordleSession, result: &GameResult) -> Result<(), std::io::Error> {
+            self.calls.push(format!("end {}", matches!(result, GameResult::Win(_))));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn mock_renderer1() {
+        let input = b"grape\napple\n";
+        let mut session = WordleSessionCLI::with_renderer(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 3), input.as_slice(), MockRenderer { calls: Vec::new() }, CliOptions::default());
+        session.run().expect("Failed to run session");
+        assert_eq!(session.renderer.calls, vec![
+            "board 0", "message ", "message Enter your word:",
+            "board 1", "message ", "message Enter your word:",
+            "end true",
+        ]);
+    }
+
+    #[test]
+    fn parse_pin1() {
+        assert_eq!(parse_pin("2=p"), Ok((1, 'p')));
+        assert!(parse_pin("0=p").is_err());
+        assert!(parse_pin("1=pp").is_err());
+        assert!(parse_pin("p").is_err());
+    }
+
+    #[test]
+    fn assist_pin1() {
+        let input = b"apple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", vec![String::from("ample"), String::from("apple"), String::from("maple")], 3), input.as_slice(), &mut output, CliOptions { assist: true, pin: vec![(0, 'a')], ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains("Possible answers: 2\n"));
+    }
+
+    #[test]
+    fn assist_answer_list1() {
+        let input = b"apple\n";
+        let mut output = Vec::new();
+        let game = WordleGame {
+            list: Arc::new(WordList { 
+                words: vec![String::from("ample"), String::from("apple"), String::from("maple")], 
+                answers: vec![String::from("apple"), String::from("maple")],
+            }),
+            ..WordleGame::new("apple", Vec::new(), 3)
+        };
+        let mut session = WordleSessionCLI::with_options(&game, input.as_slice(), &mut output, CliOptions { assist: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains("Possible answers: 2\n"));
+    }
+
+    #[test]
+    fn reveal_delay1() {
+        let conf = Config::parse_from(["wordle", "--reveal-delay", "5000"]);
+        assert_eq!(conf.cli.reveal_delay, Some(5000));
+        assert!(!conf.cli.interactive);
+
+        // Output is not interactive, so the delay must be ignored
+        let input = b"";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 2), input.as_slice(), &mut output, conf.cli);
+        assert!(matches!(session.session.guess(&String::from("grape")), Result::Ok(_)));
+        let start = Instant::now();
+        session.print_board().expect("Failed to print to output");
+        assert!(start.elapsed().as_millis() < 5000);
+    }
+
+    #[test]
+    fn print_keyboard1() {
+        let input = b"";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 2), input.as_slice(), &mut output, CliOptions { 
+            keyboard: true, 
+            keyboard_layout: Some(String::from("abc/gpz")), 
+            ..Default::default() 
+        });
+        assert!(matches!(session.session.guess(&String::from("grape")), Result::Ok(_)));
+        session.renderer.print_keyboard(&session.session).expect("Failed to print to output");
+        let mut expected_output = Vec::new();
+        writeln!(&mut expected_output, "{}a{}b{}c{}", 
+            color::Fg(color::LightYellow), 
+            style::Reset, 
+            style::Reset, 
+            style::Reset).expect("Failed to write to expected output");
+        writeln!(&mut expected_output, "{}g{}p{}z{}", 
+            color::Fg(color::LightWhite), 
+            color::Fg(color::LightYellow), 
+            style::Reset, 
+            style::Reset).expect("Failed to write to expected output");
+        
+        assert_eq!(String::from_utf8(output).expect("Output not in UTF-8"), 
+            String::from_utf8(expected_output).expect("Expected output not in UTF-8"));
+    }
+
+    #[test]
+    fn show_pool_size1() {
+        let game = WordleGame::from_config(&Config { no_repeats: true, ..Default::default() }).unwrap();
+        let input = format!("{}\n", game.word);
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&game, input.as_bytes(), &mut output, 
+            CliOptions { show_pool_size: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        let expected = format!("This puzzle has {} possible answers\n", game.answer_list().len());
+        assert_eq!(output.matches(&expected).count(), 1);
+        assert!(game.answer_list().len() < game.word_list().len());
+    }
+
+    #[test]
+    fn score_stream1() {
+        let input = b"apple grape\napple apple\napple fig\n";
+        let mut output = Vec::new();
+        score_stream(input.as_slice(), &mut output).expect("Failed to score stream");
+        assert_eq!(String::from_utf8(output).expect("Output not in UTF-8"), 
+            "XXYYG\nGGGGG\nerror: secret and guess differ in length\n");
+    }
+
+    #[test]
+    fn fill_locked_greens1() {
+        let greens = vec![(0, String::from("a")), (3, String::from("l")), (4, String::from("e"))];
+        let typed = |t: &str| -> Vec<String> { tiles(t).iter().map(|t| t.to_string()).collect() };
+        assert_eq!(fill_locked_greens(&greens, 5, &typed("pp")), "apple");
+        assert_eq!(fill_locked_greens(&greens, 5, &typed("p")), "ap_le");
+        assert_eq!(fill_locked_greens(&greens, 5, &typed("ppx")), "apple");
+        assert_eq!(fill_locked_greens(&[], 5, &typed("grape")), "grape");
+    }
+
+    #[test]
+    fn read_locked_guess1() {
+        use termion::input::TermRead;
+
+        let greens = vec![(0, String::from("a")), (3, String::from("l")), (4, String::from("e"))];
+        let read = |keys: &[u8]| read_locked_guess(keys.keys(), &greens, 5, None).expect("Failed to read keys");
+        // Enter is ignored until the open tiles are filled, and extra letters don't spill over the greens
+        assert_eq!(read(b"\npx\x7fpzz\n"), Some(String::from("apple")));
+        assert_eq!(read(b"/giveup\n"), Some(String::from("/giveup")));
+        assert_eq!(read(b"/\x7fpp\n"), Some(String::from("apple")));
+        assert_eq!(read(b"pp"), None);
+        assert_eq!(read(b"p\x03p\n"), None);
+
+        let mut echo = Vec::new();
+        assert!(read_locked_guess(b"p\n".keys(), &greens, 5, Some(&mut echo)).unwrap().is_none());
+        let row = format!("\r{}ap_le", termion::clear::CurrentLine);
+        assert_eq!(String::from_utf8(echo).unwrap(), row.repeat(2));
+    }
+
+    #[test]
+    fn lock_greens1() {
+        let input = b"angle\npp\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("angle"), String::from("apple")], 6), 
+            input.as_slice(), &mut output, CliOptions { lock_greens: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        assert_eq!(session.session.guessed_words(), vec!["angle", "apple"]);
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains("Enter the missing letters: a__le\n"));
+        assert!(output.contains("You win!"));
+    }
+
+    #[test]
+    fn normalize_input1() {
+        assert_eq!(normalize_input("\u{FEFF}ap\u{200B}ple\u{00A0}\n"), "apple");
+        assert_eq!(normalize_input("o\u{2019}er"), "o'er");
+    }
+
+    #[test]
+    fn normalize_input2() {
+        // Neither a curly quote nor a zero-width space is trimmed, so they must be cleaned up before guessing
+        let input = "o\u{2019}\u{200B}er\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::new(&WordleGame::new("o'er", vec![String::from("o'er"), String::from("over")], 1), 
+            input.as_bytes(), &mut output);
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains("You win!"));
+    }
+
+    #[test]
+    fn assist_repeated_grey1() {
+        let input = b"allee\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("allee"), String::from("apple")], 3), 
+            input.as_slice(), &mut output, CliOptions { assist: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains("Note: grey 'l' means the word has no more copies of it, not that it is absent.\n"));
+        assert!(output.contains("Note: grey 'e' means the word has no more copies of it, not that it is absent.\n"));
+    }
+
+    #[test]
+    fn assist_wasted_placements1() {
+        let input = b"grape\nplane\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape"), String::from("plane")], 3), 
+            input.as_slice(), &mut output, CliOptions { assist: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains("Warning: 'a' was already ruled out at position 3.\n"));
+        assert_eq!(output.matches("Warning:").count(), 1);
+    }
+
+    #[test]
+    fn print_board_theme() {
+        let path = std::env::temp_dir().join("wordle_print_board_theme.toml");
+        std::fs::write(&path, "correct = \"blue\"\nwrong_pos = \"#ff8000\"\nincorrect = \"red\"\nplaceholder = \"_\"\n")
+            .expect("Failed to write theme file");
+        let conf = Config::parse_from(["wordle", "--theme", path.to_str().unwrap()]);
+
+        let input = b"";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 2), input.as_slice(), &mut output, conf.cli);
+        assert!(matches!(session.session.guess(&String::from("grape")), Result::Ok(_)));
+        session.print_board().expect("Failed to print to output");
+        let mut expected_output = Vec::new();
+        writeln!(&mut expected_output, "{}g{}r{}a{}p{}e{}", 
+            color::Fg(color::Red), 
+            color::Fg(color::Red), 
+            color::Fg(color::Rgb(255, 128, 0)), 
+            color::Fg(color::Rgb(255, 128, 0)), 
+            color::Fg(color::Blue),
+            style::Reset).expect("Failed to write to expected output");
+        writeln!(&mut expected_output, "_____").expect("Failed to write to expected output");
+        
+        assert_eq!(String::from_utf8(output).expect("Output not in UTF-8"), 
+            String::from_utf8(expected_output).expect("Expected output not in UTF-8"));
+    }
+
+    #[test]
+    fn theme_invalid() {
+        let path = std::env::temp_dir().join("wordle_theme_invalid.toml");
+        std::fs::write(&path, "correct = \"chartreuse\"\n").expect("Failed to write theme file");
+        let err = Config::try_parse_from(["wordle", "--theme", path.to_str().unwrap()]).unwrap_err();
+        assert!(err.to_string().contains("Unknown color: chartreuse for correct"));
+    }
+
+    #[test]
+    fn svg_command1() {
+        let path = std::env::temp_dir().join("wordle_svg_command1.svg");
+        let input = format!("grape\n/svg {}\napple\n", path.display());
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::new(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 6), input.as_bytes(), &mut output);
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains(&format!("Board saved to {}.", path.display())));
+        assert_eq!(std::fs::read_to_string(&path).unwrap().matches("<text ").count(), 5);
+    }
+
+    #[test]
+    fn mode_command1() {
+        let input = b"grape\n/mode assist\nample\n/mode hard\nspice\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::new(&WordleGame::new("apple", 
+            vec![String::from("ample"), String::from("apple"), String::from("grape"), String::from("spice")], 6), 
+            input.as_slice(), &mut output);
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        let (before, after) = output.split_once("Assist mode on.").expect("Assist mode not turned on");
+        assert!(!before.contains("Possible answers:"));
+        assert!(after.contains("Possible answers: 2\n"));
+        assert!(after.contains("Hard mode on."));
+        assert!(after.contains("Hard mode: your guess must use the hints revealed so far."));
+        assert!(after.contains("You win!"));
+    }
+
+    /// Formatter writing rows as `word=PATTERN` tokens.
+    struct TokenFormatter;
+
+    impl FeedbackFormatter for TokenFormatter {
+        fn format_row(&self, word: &str, validity: &[LetterValidity]) -> String {
+            GuessRow { word: word.to_string(), validity: validity.to_vec() }.to_string()
+        }
+    }
+
+    #[test]
+    fn print_board_formatter() {
+        let input = b"";
+        let mut output = Vec::new();
+        let options = CliOptions::default();
+        let renderer = TermionRenderer::with_formatter(&mut output, &options, Box::new(TokenFormatter));
+        let mut session = WordleSessionCLI::with_renderer(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 2), input.as_slice(), renderer, options);
+        assert!(matches!(session.session.guess(&String::from("grape")), Result::Ok(_)));
+        session.print_board().expect("Failed to print to output");
+        
+        assert_eq!(String::from_utf8(output).expect("Output not in UTF-8"), "grape=XXYYG\n·····\n");
+    }
+
+    #[test]
+    fn hide_answers_win1() {
+        let game = WordleGame {
+            list: Arc::new(WordList { 
+                words: vec![String::from("amber"), String::from("grape")], 
+                answers: vec![String::from("ample"), String::from("apple")],
+            }),
+            hide_answers: true,
+            ..WordleGame::new("ample", Vec::new(), 6)
+        };
+        let input = b"ample\ngrape\namber\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::new(&game, input.as_slice(), &mut output);
+        session.run().expect("Failed to run session");
+        assert_eq!(session.session.guessed_words(), vec!["grape", "amber"]);
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.ends_with("You win!\nAnswer: ample\n"));
+    }
+
+    #[test]
+    fn render_end_lies1() {
+        let words: Vec<String> = ["apple", "grape", "lapse", "leapt", "plane", "pleat"].iter().map(|w| w.to_string()).collect();
+        let game = WordleGame { max_lies: 2, ..WordleGame::new("apple", words, 6) };
+        let input = b"grape\nlapse\nleapt\nplane\npleat\napple\n";
+        let mut output = Vec::new();
+        let options = CliOptions::default();
+        let renderer = TermionRenderer::with_formatter(&mut output, &options, Box::new(TokenFormatter));
+        let mut session = WordleSessionCLI::with_renderer(&game, input.as_slice(), renderer, options);
+        session.session = WordleSession::new_with_rng(&game, &mut StdRng::seed_from_u64(1));
+        session.run().expect("Failed to run session");
+        let (g, _) = session.session.lies()[0];
+        let (w, v) = &session.session.get_guesses()[g];
+        let lied = GuessRow { word: w.clone(), validity: v.clone() }.to_string();
+        let honest: Vec<String> = session.session.honest_guesses().into_iter()
+            .map(|(word, validity)| GuessRow { word, validity }.to_string())
+            .collect();
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        let (during, end) = output.rsplit_once(&termion::clear::All.to_string()).expect("Board not drawn");
+        assert!(during.contains(&lied));
+        assert!(!end.contains(&lied));
+        assert!(honest.iter().all(|row| end.contains(row)));
+    }
+
+    #[test]
+    fn kids1() {
+        let input = b"spice\ngrape\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape"), String::from("spice")], 6), 
+            input.as_slice(), &mut output, CliOptions { kids: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert_eq!(output.matches("Warmer!\n").count(), 1);
+        assert!(!output.contains("Colder!"));
+    }
+
+    #[test]
+    fn assist_max_candidates1() {
+        let input = b"apple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("ample"), String::from("angle"), String::from("apple"), String::from("maple")], 3), 
+            input.as_slice(), &mut output, CliOptions { assist: true, max_candidates: Some(3), ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.contains("Possible answers: 4\nample angle apple …\n"));
+    }
+
+    #[test]
+    fn json_format1() {
+        let conf = Config::parse_from(["wordle", "--format", "json"]);
+        assert_eq!(conf.cli.format, OutputFormat::Json);
+
+        let input = b"grape\nhello\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_renderer(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 6), input.as_slice(), JsonRenderer::new(&mut output), conf.cli);
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        let json = output.strip_suffix('\n').expect("Missing trailing newline");
+        assert!(!json.contains('\n'));
+        assert!(json.starts_with('{') && json.ends_with('}'));
+        assert!(json.contains("\"won\":true"));
+        assert!(json.contains("\"guess_count\":2"));
+        assert!(json.contains("{\"word\":\"grape\",\"pattern\":\"XXYYG\"}"));
+    }
+
+    #[test]
+    fn json_format_eof1() {
+        let input = b"grape\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_renderer(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 6), input.as_slice(), JsonRenderer::new(&mut output), 
+            CliOptions { format: OutputFormat::Json, ..Default::default() });
+        session.run().expect("Failed to run session");
+        assert!(session.session.gave_up());
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("\"won\":false,\"gave_up\":true"));
+        assert!(output.contains("\"guess_count\":1"));
+    }
+
+    #[test]
+    fn score1() {
+        let input = b"grape\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 6), 
+            input.as_slice(), &mut output, CliOptions { score: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.ends_with("You win!\nScore: 12\n"));
+    }
+
+    #[test]
+    fn giveup_command1() {
+        let input = b"grape\n/giveup\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::new(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 6), input.as_slice(), &mut output);
+        session.run().expect("Failed to run session");
+        assert!(session.session.gave_up());
+        assert_eq!(session.session.get_guesses().len(), 1);
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.ends_with("You gave up.\nAnswer: apple\n"));
+    }
+
+    #[test]
+    fn blind1() {
+        let input = b"grape\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 6), 
+            input.as_slice(), &mut output, CliOptions { blind: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        let (during, end) = output.rsplit_once("Enter your word:").expect("No prompt shown");
+        assert!(!during.contains('·'));
+        assert!(!during.contains(&format!("{}g", color::Fg(color::LightWhite))));
+        assert!(end.contains(&format!("{}g{}r", color::Fg(color::LightWhite), color::Fg(color::LightWhite))));
+        assert_eq!(end.matches("·····\n").count(), 4);
+        assert!(end.ends_with("You win!\n"));
+    }
+
+    #[test]
+    fn message_pack1() {
+        let conf = Config::parse_from(["wordle", "--message-pack", "pirate", "--message-seed", "7"]);
+        assert_eq!(conf.cli.message_pack, MessagePack::Pirate);
+
+        let run = |options: CliOptions| {
+            let input = b"apple\n";
+            let mut output = Vec::new();
+            let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+                vec![String::from("apple"), String::from("grape")], 6), input.as_slice(), &mut output, options);
+            session.run().expect("Failed to run session");
+            let output = String::from_utf8(output).expect("Output not in UTF-8");
+            output.lines().last().unwrap_or_default().to_string()
+        };
+        let message = run(conf.cli.clone());
+        assert_eq!(message, MessagePack::Pirate.win_message(&mut StdRng::seed_from_u64(7)));
+        assert_eq!(run(conf.cli), message);
+        assert_eq!(run(CliOptions::default()), "You win!");
+    }
+
+    #[test]
+    fn lies1() {
+        let conf = Config::parse_from(["wordle", "--lies", "1"]);
+        assert_eq!(conf.lies, 1);
+
+        let game = WordleGame { max_lies: 1, ..WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape"), String::from("leapt")], 6) };
+        let input = b"grape\nleapt\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&game, input.as_slice(), &mut output, 
+            CliOptions { assist: true, ..Default::default() });
+        session.session = WordleSession::new_with_rng(&game, &mut StdRng::seed_from_u64(0));
+        session.run().expect("Failed to run session");
+        // With this seed the 'p' of "grape" is shown grey
+        assert_eq!(session.session.lies(), &[(0, 3)]);
+        assert_eq!(session.session.get_guesses()[0].1[3], LetterValidity::Incorrect);
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        // The assist count would come from the lying board
+        assert!(!output.contains("Possible answers"));
+        let (_, end) = output.rsplit_once("You win!\n").expect("Game not won");
+        assert_eq!(end, "Guess 1, letter 4: 'p' was shown grey but is in the word.\n");
+    }
+
+    #[test]
+    fn assist_greens_count1() {
+        let conf = Config::parse_from(["wordle", "--assist-greens-count"]);
+        let input = b"angle\ngrape\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("angle"), String::from("apple"), String::from("grape")], 6), 
+            input.as_slice(), &mut output, conf.cli);
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        let (during, end) = output.rsplit_once("Enter your word:").expect("No prompt shown");
+        assert!(during.contains("angle  Greens: 3\ngrape  Greens: 1\n"));
+        assert!(!during.contains(&format!("{}", color::Fg(color::LightGreen))));
+        assert!(end.contains(&format!("{}g{}r", color::Fg(color::LightWhite), color::Fg(color::LightWhite))));
+        assert!(end.ends_with("You win!\n"));
+    }
+
+    #[test]
+    fn retry_same1() {
+        let input = b"grape\ny\napple\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 1), 
+            input.as_slice(), &mut output, CliOptions { retry_same: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        assert_eq!(session.session.game.word, "apple");
+        assert_eq!(session.session.get_guesses().len(), 1);
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert_eq!(output.matches("Try the same word again?").count(), 1);
+        assert!(!output.contains("Answer: apple"));
+        assert!(output.contains("You win!"));
+    }
+
+    #[test]
+    fn retry_same2() {
+        let input = b"grape\nn\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("grape")], 1), 
+            input.as_slice(), &mut output, CliOptions { retry_same: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.ends_with("Answer: apple\n"));
+    }
+
+    #[test]
+    fn show_discovery1() {
+        let conf = Config::parse_from(["wordle", "--show-discovery"]);
+        assert!(conf.cli.show_discovery);
+
+        let input = b"angle\nspice\nhello\n";
+        let mut output = Vec::new();
+        let mut session = WordleSessionCLI::with_options(&WordleGame::new("apple", 
+            vec![String::from("apple"), String::from("angle"), String::from("spice"), String::from("hello")], 3), 
+            input.as_slice(), &mut output, CliOptions { show_discovery: true, ..Default::default() });
+        session.run().expect("Failed to run session");
+        let output = String::from_utf8(output).expect("Output not in UTF-8");
+        assert!(output.ends_with("Answer: apple\nFound on guess: 1 2 - 1 1\n"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,188 @@
+//! Word of the day selection.
+//!
+//! The daily word depends only on the day index, so every player gets the same word on the same day.
+//! Words of days that haven't started yet are never revealed through the public API.
+use std::{time::{SystemTime, UNIX_EPOCH}, fmt, error::Error, fs, io, path::Path};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Error produced when asking for the word of a day that isn't available.
+#[derive(PartialEq, Eq, Debug)]
+pub enum DailyError {
+    /// The requested day hasn't started yet
+    FutureDay,
+
+    /// There are no answers to choose from
+    NoAnswers,
+}
+
+impl fmt::Display for DailyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DailyError::FutureDay => write!(f, "That day's word isn't available yet"),
+            DailyError::NoAnswers => write!(f, "No answers to choose the daily word from"),
+        }
+    }
+}
+
+impl Error for DailyError {}
+
+/// Get the number of whole days (UTC) between the Unix epoch and `time`.
+pub fn day_index(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() / SECONDS_PER_DAY)
+}
+
+/// Get today's day index.
+pub fn today() -> u64 {
+    day_index(SystemTime::now())
+}
+
+/// Get the word of the day from `answers`.
+pub fn daily_word(answers: &[String]) -> Result<&String, DailyError> {
+    word_for_day(answers, today())
+}
+
+/// Get the word of `day` from `answers`, refusing days after today.
+pub fn word_for_day(answers: &[String], day: u64) -> Result<&String, DailyError> {
+    if day > today() {
+        return Err(DailyError::FutureDay);
+    }
+    pick(answers, day)
+}
+
+/// Get the word of any `day` from `answers`, including days that haven't started yet.
+#[cfg(any(test, feature = "daily-override"))]
+pub fn word_for_day_unchecked(answers: &[String], day: u64) -> Result<&String, DailyError> {
+    pick(answers, day)
+}
+
+/// Get today's word from `answers`, skipping any word in `recent`.
+/// If every answer is recent, the word is chosen as if nothing were excluded.
+pub fn daily_word_excluding<'a>(answers: &'a [String], recent: &[String]) -> Result<&'a String, DailyError> {
+    let first = daily_word(answers)?;
+    let start = answers.iter().position(|w| std::ptr::eq(w, first)).unwrap_or(0);
+    Ok((0..answers.len())
+        .map(|i| &answers[(start + i) % answers.len()])
+        .find(|w| !recent.contains(w))
+        .unwrap_or(first))
+}
+
+/// Previous daily answers, stored as `DAY WORD` lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    entries: Vec<(u64, String)>,
+}
+
+impl History {
+    /// Load the history from `path`. A missing file is an empty history.
+    pub fn load(path: &Path) -> Result<History, io::Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(History::default()),
+            Err(e) => return Err(e),
+        };
+        let entries = text.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                let (day, word) = l.trim().split_once(' ')
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid history line: {}", l)))?;
+                let day = day.parse()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid history day: {}", day)))?;
+                Ok((day, word.to_string()))
+            })
+            .collect::<Result<_, io::Error>>()?;
+        Ok(History { entries })
+    }
+
+    /// Save the history to `path`.
+    pub fn save(&self, path: &Path) -> Result<(), io::Error> {
+        let text: String = self.entries.iter().map(|(day, word)| format!("{} {}\n", day, word)).collect();
+        fs::write(path, text)
+    }
+
+    /// Get the answer recorded for `day`, if any.
+    pub fn word_on(&self, day: u64) -> Option<&String> {
+        self.entries.iter().find(|(d, _)| *d == day).map(|(_, w)| w)
+    }
+
+    /// Get the answers of the `n` most recent days before `day`.
+    pub fn recent(&self, n: usize, day: u64) -> Vec<String> {
+        let mut earlier: Vec<&(u64, String)> = self.entries.iter().filter(|(d, _)| *d < day).collect();
+        earlier.sort_by_key(|(d, _)| std::cmp::Reverse(*d));
+        earlier.into_iter().take(n).map(|(_, w)| w.clone()).collect()
+    }
+
+    /// Record `word` as the answer of `day`, replacing any earlier record for that day.
+    pub fn record(&mut self, day: u64, word: &str) {
+        self.entries.retain(|(d, _)| *d != day);
+        self.entries.push((day, word.to_string()));
+    }
+}
+
+/// Pick the word for `day` by scrambling the day index, so consecutive days aren't neighbours in the list.
+fn pick(answers: &[String], day: u64) -> Result<&String, DailyError> {
+    if answers.is_empty() {
+        return Err(DailyError::NoAnswers);
+    }
+    // SplitMix64 finalizer
+    let mut x = day.wrapping_add(0x9E3779B97F4A7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^= x >> 31;
+    Ok(&answers[(x % answers.len() as u64) as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{UNIX_EPOCH, Duration};
+
+    use super::{day_index, today, daily_word, word_for_day, word_for_day_unchecked, daily_word_excluding, DailyError, History};
+
+    fn answers() -> Vec<String> {
+        ["apple", "grape", "lemon", "mango", "peach", "melon", "guava"].iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn day_index1() {
+        assert_eq!(day_index(UNIX_EPOCH), 0);
+        assert_eq!(day_index(UNIX_EPOCH + Duration::from_secs(2 * 86400 - 1)), 1);
+    }
+
+    #[test]
+    fn daily_word1() {
+        let answers = answers();
+        assert_eq!(daily_word(&answers), word_for_day_unchecked(&answers, today()));
+        assert_eq!(daily_word(&answers), word_for_day(&answers, today()));
+        assert_eq!(word_for_day(&answers, today() + 1), Err(DailyError::FutureDay));
+        assert!(word_for_day_unchecked(&answers, today() + 1).is_ok());
+        assert_eq!(daily_word(&[]), Err(DailyError::NoAnswers));
+    }
+
+    #[test]
+    fn daily_word_excluding1() {
+        let answers = answers();
+        let usual = daily_word(&answers).unwrap().clone();
+        let recent = vec![usual.clone(), String::from("lemon")];
+        let chosen = daily_word_excluding(&answers, &recent).unwrap();
+        assert!(!recent.contains(chosen));
+        assert_eq!(daily_word_excluding(&answers, &[]).unwrap(), &usual);
+        assert_eq!(daily_word_excluding(&answers, &answers).unwrap(), &usual);
+    }
+
+    #[test]
+    fn history1() {
+        let path = std::env::temp_dir().join("wordle_history1.txt");
+        let mut history = History::default();
+        history.record(10, "apple");
+        history.record(11, "grape");
+        history.record(12, "lemon");
+        history.record(12, "mango");
+        history.save(&path).unwrap();
+
+        let history = History::load(&path).unwrap();
+        assert_eq!(history.recent(2, 13), vec![String::from("mango"), String::from("grape")]);
+        assert_eq!(history.recent(5, 11), vec![String::from("apple")]);
+        assert_eq!(history.word_on(12), Some(&String::from("mango")));
+        assert_eq!(History::load(&std::env::temp_dir().join("wordle_history_missing.txt")).unwrap(), History::default());
+    }
+}
\ No newline at end of file
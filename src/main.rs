@@ -1,17 +1,60 @@
-use std::io;
+use std::{io, process};
 
 use clap::Parser;
 
-use wordle::{Config, WordleGame, cli::WordleSessionCLI};
+use wordle::{Cli, Command, Config, WordleGame, cli::{self, WordleSessionCLI, JsonRenderer, OutputFormat}, solver};
 
 fn main() {
-    let conf = Config::parse();
+    match Cli::parse().into_command() {
+        Command::Play(conf) => play(&conf),
+        Command::Daily(mut conf) => {
+            conf.daily = true;
+            play(&conf)
+        },
+        Command::Validate(conf) => validate(&conf),
+        Command::Analyze(conf) => analyze(&conf),
+        Command::ScoreStream => cli::score_stream(io::stdin().lock(), io::stdout()).expect("Error scoring input"),
+    }
+}
 
-    let game = WordleGame::from_config(&conf).expect("Error initializing game");
-    println!("Word: {}", &game.word);    
+/// Play a game in the terminal.
+fn play(conf: &Config) {
+    let game = WordleGame::from_config(conf).expect("Error initializing game");
 
     let input = io::stdin().lock();
     let output = io::stdout();
-    let mut session = WordleSessionCLI::new(&game, input, output);
+    let mut options = conf.cli.clone();
+    if options.format == OutputFormat::Json {
+        let mut session = WordleSessionCLI::with_renderer(&game, input, JsonRenderer::new(output), options);
+        session.run().expect("Error in Wordle session");
+        return;
+    }
+
+    options.interactive = termion::is_tty(&output);
+    let mut session = WordleSessionCLI::with_options(&game, input, output, options);
     session.run().expect("Error in Wordle session");
-}
\ No newline at end of file
+}
+
+/// Check that the word file loads, exiting with an error if it doesn't.
+fn validate(conf: &Config) {
+    match WordleGame::from_config(conf) {
+        Ok(_) => println!("Word file is valid"),
+        Err(e) => {
+            eprintln!("Invalid word file: {}", e);
+            process::exit(1);
+        },
+    }
+}
+
+/// List the answers the solver cannot find within the max guesses.
+fn analyze(conf: &Config) {
+    let game = WordleGame::from_config(conf).expect("Error initializing game");
+    let unsolvable = solver::audit_answers(game.answer_list(), game.max_guesses);
+    for w in unsolvable.iter() {
+        println!("{}", w);
+    }
+    println!("{} of {} answers not solvable within {} guesses", unsolvable.len(), game.answer_list().len(), game.max_guesses);
+    if let Some(kind) = conf.solver {
+        println!("Average guesses ({:?}): {:.3}", kind, solver::average_guesses(kind.strategy().as_ref(), &game));
+    }
+}
@@ -0,0 +1,458 @@
+//! Helpers for narrowing down the possible answers from the board.
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+
+use crate::{FeedbackLevel, LetterValidity, WordleGame, score_guess};
+
+/// Extra hypotheses about the answer, applied on top of the board.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Constraints {
+    /// Letters that must be at the given position
+    pub greens: Vec<(usize, char)>,
+
+    /// Letters that must be in the word, but not at the given position
+    pub yellows: Vec<(usize, char)>,
+
+    /// Letters that must not be in the word
+    pub absent: Vec<char>,
+}
+
+impl Constraints {
+    /// Check whether `word` satisfies every constraint.
+    pub fn matches(&self, word: &str) -> bool {
+        let letters: Vec<char> = word.chars().collect();
+        self.greens.iter().all(|&(i, c)| letters.get(i) == Some(&c))
+            && self.yellows.iter().all(|&(i, c)| letters.get(i) != Some(&c) && letters.contains(&c))
+            && self.absent.iter().all(|c| !letters.contains(c))
+    }
+}
+
+/// Candidate words, possibly cut short of every matching word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateList {
+    /// Matching words in alphabetical order, up to the requested limit
+    pub words: Vec<String>,
+
+    /// Number of matching words, including any left out of `words`
+    pub total: usize,
+}
+
+impl CandidateList {
+    /// Check whether some matching words were left out of `words`.
+    pub fn has_more(&self) -> bool {
+        self.total > self.words.len()
+    }
+}
+
+/// Get the words from `words` that are consistent with every guess in `guesses` and with `extra`.
+/// If `limit` is set, only the first `limit` matches in alphabetical order are returned.
+pub fn filter_candidates(words: &[String], guesses: &[(String, Vec<LetterValidity>)], extra: &Constraints, limit: Option<usize>) -> CandidateList {
+    filter_candidates_with_feedback(words, guesses, FeedbackLevel::Full, extra, limit)
+}
+
+/// Like `filter_candidates`, for guesses whose feedback was shown at `feedback` level.
+pub fn filter_candidates_with_feedback(words: &[String], guesses: &[(String, Vec<LetterValidity>)], feedback: FeedbackLevel, 
+    extra: &Constraints, limit: Option<usize>) -> CandidateList {
+    let mut matches: Vec<&String> = words.iter()
+        .filter(|w| guesses.iter().all(|(g, v)| feedback.apply(score_guess(w, g)) == *v))
+        .filter(|w| extra.matches(w))
+        .collect();
+    matches.sort_unstable();
+    let total = matches.len();
+    matches.truncate(limit.unwrap_or(total));
+    CandidateList { words: matches.into_iter().cloned().collect(), total }
+}
+
+/// Encode a feedback pattern as a base-3 number, where position `i` contributes 
+/// 0 (incorrect), 1 (wrong position) or 2 (correct) times `3^i`.
+pub fn pattern_code(validity: &[LetterValidity]) -> u32 {
+    validity.iter().rev().fold(0, |code, lv| code * 3 + match lv {
+        LetterValidity::Incorrect => 0,
+        LetterValidity::WrongPos => 1,
+        LetterValidity::Correct => 2,
+    })
+}
+
+/// Read the rows of a shared emoji grid (see `WordleSession::share_text`) as letter validity, one row per guess.
+/// `🟩` is correct, `🟨` is in the wrong position and `⬛` or `⬜` is incorrect; the high contrast `🟧` and `🟦` 
+/// are read as correct and wrong position. Lines with anything else, such as the header, are skipped.
+pub fn parse_share_grid(text: &str) -> Vec<Vec<LetterValidity>> {
+    text.lines()
+        .filter_map(|line| {
+            let row = line.trim().chars()
+                .map(|c| match c {
+                    '🟩' | '🟧' => Some(LetterValidity::Correct),
+                    '🟨' | '🟦' => Some(LetterValidity::WrongPos),
+                    '⬛' | '⬜' => Some(LetterValidity::Incorrect),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            (!row.is_empty()).then_some(row)
+        })
+        .collect()
+}
+
+/// Remembers the `pattern_code` of each guess scored against one secret, so repeated guesses aren't scored again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreCache {
+    secret: String,
+    codes: HashMap<String, u32>,
+    hits: usize,
+}
+
+impl ScoreCache {
+    /// Create an empty `ScoreCache` for `secret`.
+    pub fn new(secret: &str) -> ScoreCache {
+        ScoreCache { secret: secret.to_string(), codes: HashMap::new(), hits: 0 }
+    }
+
+    /// Get the secret the guesses are scored against.
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Get the `pattern_code` of `guess` scored against the secret, scoring it only the first time.
+    pub fn pattern_code(&mut self, guess: &str) -> u32 {
+        if let Some(&code) = self.codes.get(guess) {
+            self.hits += 1;
+            return code;
+        }
+        let code = pattern_code(&score_guess(&self.secret, guess));
+        self.codes.insert(guess.to_string(), code);
+        code
+    }
+
+    /// Get how many times a remembered code was returned instead of scoring the guess.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+/// Count how many of `candidates` would produce each feedback pattern for `guess`, keyed by `pattern_code`.
+pub fn pattern_distribution(guess: &str, candidates: &[String]) -> HashMap<u32, usize> {
+    let mut distribution: HashMap<u32, usize> = HashMap::new();
+    for w in candidates {
+        *distribution.entry(pattern_code(&score_guess(w, guess))).or_default() += 1;
+    }
+    distribution
+}
+
+/// Pick the candidate that leaves the fewest candidates on average once its feedback is known.
+/// Ties are broken by the order of `candidates`.
+pub fn best_guess(candidates: &[String]) -> Option<&String> {
+    candidates.iter().min_by_key(|g| {
+        pattern_distribution(g, candidates).values().map(|n| n * n).sum::<usize>()
+    })
+}
+
+/// Pick the word from `guesses` whose largest group of `candidates` sharing a feedback pattern is smallest.
+/// Ties go to words that may be the answer, then to the order of `guesses`.
+pub fn minimax_guess(guesses: &[String], candidates: &[String]) -> Option<String> {
+    if candidates.is_empty() {
+        return None;
+    }
+    guesses.iter()
+        .min_by_key(|g| {
+            let worst = pattern_distribution(g, candidates).values().copied().max().unwrap_or(0);
+            (worst, !candidates.contains(g))
+        })
+        .cloned()
+}
+
+/// Count the guesses the solver needs to find `answer` in `words`, opening with `opener`.
+/// `caches` holds a `ScoreCache` for each word of `words`, in the same order.
+fn guess_count_from(answer: &str, words: &[String], opener: &str, max_guesses: u32, caches: &mut [ScoreCache]) -> Option<u32> {
+    let mut candidates: Vec<usize> = (0..words.len()).collect();
+    let mut guess = opener.to_string();
+    for n in 1..=max_guesses {
+        if guess == answer {
+            return Some(n);
+        }
+        let feedback = pattern_code(&score_guess(answer, &guess));
+        candidates.retain(|&i| caches[i].pattern_code(&guess) == feedback);
+        let remaining: Vec<String> = candidates.iter().map(|&i| words[i].clone()).collect();
+        guess = best_guess(&remaining)?.clone();
+    }
+    None
+}
+
+/// Create an empty `ScoreCache` for each word of `words`.
+fn score_caches(words: &[String]) -> Vec<ScoreCache> {
+    words.iter().map(|w| ScoreCache::new(w)).collect()
+}
+
+/// Count the guesses the solver needs to find `answer` in `words`.
+/// Returns `None` if the solver cannot find it within `max_guesses`.
+pub fn optimal_guess_count(answer: &str, words: &[String], max_guesses: u32) -> Option<u32> {
+    let opener = best_guess(words)?;
+    guess_count_from(answer, words, opener, max_guesses, &mut score_caches(words))
+}
+
+/// Get the answers in `words` that the solver cannot find within `max_guesses`.
+pub fn audit_answers(words: &[String], max_guesses: u32) -> Vec<String> {
+    let opener = match best_guess(words) {
+        Some(w) => w,
+        None => return Vec::new(),
+    };
+    // Every answer starts with the same opener, so the caches save most of the scoring
+    let mut caches = score_caches(words);
+    words.iter()
+        .filter(|a| guess_count_from(a, words, opener, max_guesses, &mut caches).is_none())
+        .cloned()
+        .collect()
+}
+
+/// A way of choosing the next guess from the board.
+pub trait Strategy {
+    /// Choose the next guess, given the guesses so far and the answers still consistent with them.
+    fn next_guess(&self, guesses: &[(String, Vec<LetterValidity>)], candidates: &[String]) -> Option<String>;
+}
+
+/// Guesses the candidate whose feedback carries the most information (Shannon entropy).
+/// Ties are broken by the order of the candidates.
+pub struct EntropyStrategy;
+
+impl Strategy for EntropyStrategy {
+    fn next_guess(&self, _guesses: &[(String, Vec<LetterValidity>)], candidates: &[String]) -> Option<String> {
+        let total = candidates.len() as f64;
+        let entropy = |g: &String| -> f64 {
+            pattern_distribution(g, candidates).values()
+                .map(|&n| { let p = n as f64 / total; -p * p.log2() })
+                .sum()
+        };
+        let mut best: Option<(&String, f64)> = None;
+        for g in candidates {
+            let e = entropy(g);
+            if best.is_none_or(|(_, b)| e > b) {
+                best = Some((g, e));
+            }
+        }
+        best.map(|(g, _)| g.clone())
+    }
+}
+
+/// Guesses the candidate that leaves the fewest answers in the worst case (minimax).
+pub struct MinimaxStrategy;
+
+impl Strategy for MinimaxStrategy {
+    fn next_guess(&self, _guesses: &[(String, Vec<LetterValidity>)], candidates: &[String]) -> Option<String> {
+        minimax_guess(candidates, candidates)
+    }
+}
+
+/// Strategies that can be chosen from the command line.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolverKind {
+    /// Most information on average (`EntropyStrategy`)
+    #[default]
+    Entropy,
+
+    /// Fewest answers left in the worst case (`MinimaxStrategy`)
+    Minimax,
+}
+
+impl SolverKind {
+    /// Get the strategy of this kind.
+    pub fn strategy(&self) -> Box<dyn Strategy> {
+        match self {
+            SolverKind::Entropy => Box::new(EntropyStrategy),
+            SolverKind::Minimax => Box::new(MinimaxStrategy),
+        }
+    }
+}
+
+/// Get the average number of guesses `strategy` needs to find each answer of `game`.
+/// Answers not found within `game.max_guesses` count as `max_guesses + 1`.
+pub fn average_guesses(strategy: &dyn Strategy, game: &WordleGame) -> f64 {
+    if game.answer_list().is_empty() {
+        return 0.0;
+    }
+    let max_guesses = game.max_guesses_usize();
+    // The opener only depends on the full answer list, so it is chosen once and shared by every answer
+    let opener = strategy.next_guess(&[], game.answer_list());
+    let total: usize = game.answer_list().iter()
+        .map(|answer| {
+            let mut candidates = game.answer_list().to_vec();
+            let mut guesses: Vec<(String, Vec<LetterValidity>)> = Vec::new();
+            while guesses.len() < max_guesses {
+                let next = if guesses.is_empty() { opener.clone() } else { strategy.next_guess(&guesses, &candidates) };
+                let guess = match next {
+                    Some(g) => g,
+                    None => break,
+                };
+                if guess == *answer {
+                    return guesses.len() + 1;
+                }
+                let feedback = score_guess(answer, &guess);
+                candidates.retain(|w| score_guess(w, &guess) == feedback);
+                guesses.push((guess, feedback));
+            }
+            max_guesses.saturating_add(1)
+        })
+        .sum();
+    total as f64 / game.answer_list().len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{score_guess, LetterValidity, WordleGame, WordleSession};
+
+    use super::{Constraints, filter_candidates, optimal_guess_count, audit_answers, pattern_code, pattern_distribution, 
+        average_guesses, minimax_guess, parse_share_grid, EntropyStrategy, ScoreCache, MinimaxStrategy, SolverKind, Strategy};
+
+    fn words() -> Vec<String> {
+        ["ample", "angle", "apple", "grape", "maple"].iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn filter_candidates1() {
+        let guesses = vec![(String::from("grape"), score_guess("apple", "grape"))];
+        let r = filter_candidates(&words(), &guesses, &Constraints::default(), None).words;
+        assert_eq!(r, vec![String::from("ample"), String::from("apple"), String::from("maple")]);
+    }
+
+    #[test]
+    fn filter_candidates2() {
+        let constraints = Constraints { greens: vec![(1, 'm')], ..Default::default() };
+        let r = filter_candidates(&words(), &[], &constraints, None).words;
+        assert_eq!(r, vec![String::from("ample")]);
+    }
+
+    #[test]
+    fn filter_candidates3() {
+        let guesses = vec![(String::from("grape"), score_guess("apple", "grape"))];
+        let constraints = Constraints { greens: vec![(1, 'p')], ..Default::default() };
+        let r = filter_candidates(&words(), &guesses, &constraints, None).words;
+        assert_eq!(r, vec![String::from("apple")]);
+    }
+
+    #[test]
+    fn filter_candidates_limit() {
+        let r = filter_candidates(&words(), &[], &Constraints::default(), Some(3));
+        assert_eq!(r.words, vec![String::from("ample"), String::from("angle"), String::from("apple")]);
+        assert_eq!(r.total, 5);
+        assert!(r.has_more());
+        assert!(!filter_candidates(&words(), &[], &Constraints::default(), Some(5)).has_more());
+    }
+
+    #[test]
+    fn optimal_guess_count1() {
+        let words: Vec<String> = ["bills", "fills", "hills"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(optimal_guess_count("bills", &words, 2), Some(1));
+        assert_eq!(optimal_guess_count("fills", &words, 2), Some(2));
+        assert_eq!(optimal_guess_count("hills", &words, 2), None);
+        assert_eq!(optimal_guess_count("hills", &words, 3), Some(3));
+    }
+
+    #[test]
+    fn audit_answers1() {
+        let words: Vec<String> = ["bills", "fills", "hills"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(audit_answers(&words, 2), vec![String::from("hills")]);
+        assert!(audit_answers(&words, 3).is_empty());
+    }
+
+    #[test]
+    fn pattern_code1() {
+        use LetterValidity::*;
+
+        assert_eq!(pattern_code(&[Incorrect, Incorrect, Incorrect, Incorrect, Incorrect]), 0);
+        assert_eq!(pattern_code(&[Correct, Correct, Correct, Correct, Correct]), 242);
+        assert_eq!(pattern_code(&[WrongPos, Correct, Incorrect, Incorrect, Incorrect]), 1 + 2 * 3);
+    }
+
+    #[test]
+    fn parse_share_grid1() {
+        use LetterValidity::*;
+
+        let rows = parse_share_grid("Wordle 1,234 2/6\n\n⬛⬜🟨🟨🟩\n🟩🟩🟩🟩🟩\n");
+        assert_eq!(rows, vec![
+            vec![Incorrect, Incorrect, WrongPos, WrongPos, Correct],
+            vec![Correct, Correct, Correct, Correct, Correct],
+        ]);
+
+        let mut ws = WordleSession::new(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 6));
+        assert!(ws.guess("grape").is_ok());
+        let expected: Vec<Vec<LetterValidity>> = ws.get_guesses().iter().map(|(_, v)| v.clone()).collect();
+        assert_eq!(parse_share_grid(&ws.share_with_spoiler()), expected);
+    }
+
+    #[test]
+    fn score_cache1() {
+        let words = words();
+        for secret in words.iter() {
+            let mut cache = ScoreCache::new(secret);
+            assert_eq!(cache.secret(), secret);
+            for guess in words.iter().chain(words.iter()) {
+                assert_eq!(cache.pattern_code(guess), pattern_code(&score_guess(secret, guess)));
+            }
+            assert_eq!(cache.hits(), words.len());
+        }
+    }
+
+    #[test]
+    fn pattern_distribution1() {
+        let r = pattern_distribution("grape", &words());
+        // "apple", "ample" and "maple" all score ⬛⬛🟨🟨🟩 against "grape"
+        let shared = pattern_code(&score_guess("apple", "grape"));
+        assert_eq!(r, HashMap::from([
+            (pattern_code(&score_guess("angle", "grape")), 1),
+            (shared, 3),
+            (242, 1),
+        ]));
+        assert_eq!(r.values().sum::<usize>(), words().len());
+    }
+
+    #[test]
+    fn entropy_strategy1() {
+        // "ample" splits the other words into separate patterns, "grape" can't tell three of them apart
+        let words = words();
+        assert_eq!(EntropyStrategy.next_guess(&[], &words), Some(String::from("ample")));
+        assert_eq!(EntropyStrategy.next_guess(&[], &[]), None);
+    }
+
+    #[test]
+    fn minimax_guess1() {
+        let words: Vec<String> = ["barns", "earns", "ferns", "horns", "kerns", "morns", "tarns", "terns", "yarns"].iter()
+            .map(|w| w.to_string()).collect();
+        // "terns" gives the most information on average, but can leave four words together; "earns" leaves at most three
+        assert_eq!(EntropyStrategy.next_guess(&[], &words), Some(String::from("terns")));
+        assert_eq!(minimax_guess(&words, &words), Some(String::from("earns")));
+        assert_eq!(MinimaxStrategy.next_guess(&[], &words), Some(String::from("earns")));
+        assert_eq!(SolverKind::Minimax.strategy().next_guess(&[], &words), Some(String::from("earns")));
+        assert_eq!(minimax_guess(&words, &[]), None);
+    }
+
+    #[test]
+    fn average_guesses1() {
+        let words: Vec<String> = ["bills", "fills", "hills"].iter().map(|w| w.to_string()).collect();
+        let game = WordleGame::new("bills", words, 6);
+        assert_eq!(average_guesses(&EntropyStrategy, &game), 2.0);
+        assert_eq!(average_guesses(&EntropyStrategy, &game), average_guesses(&EntropyStrategy, &game));
+
+        // "hills" is only found on the third guess, so it counts as max_guesses + 1
+        let game = WordleGame { max_guesses: 2, ..game };
+        assert_eq!(average_guesses(&EntropyStrategy, &game), (1.0 + 2.0 + 3.0) / 3.0);
+    }
+
+    #[test]
+    fn average_guesses_opener1() {
+        struct CountOpeners(std::cell::Cell<usize>);
+
+        impl Strategy for CountOpeners {
+            fn next_guess(&self, guesses: &[(String, Vec<LetterValidity>)], candidates: &[String]) -> Option<String> {
+                if guesses.is_empty() {
+                    self.0.set(self.0.get() + 1);
+                }
+                EntropyStrategy.next_guess(guesses, candidates)
+            }
+        }
+
+        let words: Vec<String> = ["bills", "fills", "hills"].iter().map(|w| w.to_string()).collect();
+        let strategy = CountOpeners(std::cell::Cell::new(0));
+        assert_eq!(average_guesses(&strategy, &WordleGame::new("bills", words, 6)), 2.0);
+        assert_eq!(strategy.0.get(), 1);
+    }
+}
\ No newline at end of file
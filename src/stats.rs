@@ -0,0 +1,112 @@
+//! Summaries of finished games for exporting.
+use crate::{GuessRow, WordleSession};
+
+/// Summary of a game, for exporting to other tools.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameStats {
+    /// `WordleSession::session_id` of the summarized session
+    pub session_id: u64,
+    pub word: String,
+    pub won: bool,
+    /// Whether the game was lost by giving up rather than running out of guesses
+    pub gave_up: bool,
+    pub max_guesses: u32,
+    pub guesses: Vec<GuessRow>,
+}
+
+impl GameStats {
+    /// Summarize the guesses made so far in `session`, with the true validity of any tiles that were lied about.
+    pub fn from_session(session: &WordleSession) -> GameStats {
+        GameStats {
+            session_id: session.session_id(),
+            word: session.game.word.clone(),
+            won: session.is_won(),
+            gave_up: session.gave_up(),
+            max_guesses: session.game.max_guesses,
+            guesses: session.honest_guesses().into_iter()
+                .map(|(word, validity)| GuessRow { word, validity })
+                .collect(),
+        }
+    }
+
+    /// Write the stats as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self.guesses.iter()
+            .map(|r| {
+                let pattern: String = r.validity.iter().map(|lv| lv.to_char()).collect();
+                format!("{{\"word\":{},\"pattern\":\"{}\"}}", json_string(&r.word), pattern)
+            })
+            .collect();
+        // The id is written as a hex string, since JSON readers may not keep every digit of a large number
+        format!("{{\"session_id\":\"{:016x}\",\"word\":{},\"won\":{},\"gave_up\":{},\"guess_count\":{},\"max_guesses\":{},\"guesses\":[{}]}}",
+            self.session_id, json_string(&self.word), self.won, self.gave_up, self.guesses.len(), self.max_guesses, rows.join(","))
+    }
+}
+
+/// Quote `s` as a JSON string.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{WordleGame, WordleSession, score_guess};
+
+    use super::{GameStats, json_string};
+
+    #[test]
+    fn json_string1() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+
+    #[test]
+    fn to_json1() {
+        let mut ws = WordleSession::new(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 6));
+        assert!(ws.guess("grape").is_ok());
+        assert!(ws.guess("apple").is_ok());
+        assert_eq!(GameStats::from_session(&ws).to_json(), format!(
+            "{{\"session_id\":\"{:016x}\",\"word\":\"apple\",\"won\":true,\"gave_up\":false,\"guess_count\":2,\"max_guesses\":6,\"guesses\":[\
+            {{\"word\":\"grape\",\"pattern\":\"XXYYG\"}},{{\"word\":\"apple\",\"pattern\":\"GGGGG\"}}]}}", ws.session_id()));
+    }
+
+    #[test]
+    fn from_session_gave_up() {
+        let mut ws = WordleSession::new(&WordleGame::new("apple", vec![String::from("apple"), String::from("grape")], 6));
+        assert!(ws.guess("grape").is_ok());
+        ws.give_up();
+        let stats = GameStats::from_session(&ws);
+        assert!(!stats.won);
+        assert!(stats.gave_up);
+        assert!(stats.to_json().contains("\"won\":false,\"gave_up\":true"));
+    }
+
+    #[test]
+    fn from_session_lies() {
+        let words: Vec<String> = ["apple", "grape", "lapse", "leapt", "plane", "pleat"].iter().map(|w| w.to_string()).collect();
+        let game = WordleGame { max_lies: 2, ..WordleGame::new("apple", words, 6) };
+        let mut ws = WordleSession::new_with_rng(&game, &mut StdRng::seed_from_u64(1));
+        for w in ["grape", "lapse", "leapt", "plane", "pleat"] {
+            assert!(ws.guess(w).is_ok());
+        }
+        assert!(!ws.lies().is_empty());
+        let stats = GameStats::from_session(&ws);
+        for row in stats.guesses.iter() {
+            assert_eq!(row.validity, score_guess("apple", &row.word));
+        }
+    }
+}
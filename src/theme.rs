@@ -0,0 +1,253 @@
+//! Board colors, loadable from a theme file.
+use std::{collections::HashMap, fs};
+
+use termion::color;
+
+use crate::LetterValidity;
+
+/// A terminal color used by a `Theme`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThemeColor {
+    /// One of the 16 standard terminal colors, from black (0) to light white (15)
+    Ansi(u8),
+
+    /// A 24-bit color
+    Rgb(u8, u8, u8),
+}
+
+/// Names of the 16 standard terminal colors, indexed by their ANSI value.
+const ANSI_NAMES: [&str; 16] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    "lightblack", "lightred", "lightgreen", "lightyellow", "lightblue", "lightmagenta", "lightcyan", "lightwhite",
+];
+
+/// RGB values of the 16 standard terminal colors in the xterm palette, indexed by their ANSI value.
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+impl ThemeColor {
+    /// Parse a color name such as `light_green`, or a `#rrggbb` hex color.
+    pub fn parse(s: &str) -> Result<ThemeColor, String> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(ThemeColor::Rgb(r, g, b)),
+                _ => Err(format!("Invalid hex color: {}", s)),
+            };
+        }
+        let name: String = s.chars().filter(|c| !matches!(c, '_' | '-' | ' ')).collect::<String>().to_lowercase();
+        ANSI_NAMES.iter().position(|n| *n == name)
+            .map(|i| ThemeColor::Ansi(i as u8))
+            .ok_or_else(|| format!("Unknown color: {}", s))
+    }
+
+    /// Get the name of this color as accepted by `ThemeColor::parse`.
+    pub fn name(&self) -> String {
+        match *self {
+            ThemeColor::Ansi(n) => ANSI_NAMES.get(n as usize).map_or_else(|| format!("ansi{}", n), |s| s.to_string()),
+            ThemeColor::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+
+    /// Get this color as `#rrggbb`, using the xterm palette for ANSI colors.
+    pub fn hex(&self) -> String {
+        let (r, g, b) = match *self {
+            ThemeColor::Ansi(n) if n < 16 => ANSI_RGB[n as usize],
+            // 6x6x6 color cube
+            ThemeColor::Ansi(n) if n < 232 => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let n = n - 16;
+                (level(n / 36), level(n / 6 % 6), level(n % 6))
+            },
+            // Grayscale ramp
+            ThemeColor::Ansi(n) => {
+                let v = 8 + (n - 232) * 10;
+                (v, v, v)
+            },
+            ThemeColor::Rgb(r, g, b) => (r, g, b),
+        };
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// Get the escape sequence setting this color as the terminal foreground.
+    pub fn fg(&self) -> String {
+        match *self {
+            ThemeColor::Ansi(n) => color::Fg(color::AnsiValue(n)).to_string(),
+            ThemeColor::Rgb(r, g, b) => color::Fg(color::Rgb(r, g, b)).to_string(),
+        }
+    }
+}
+
+/// Colors for each `LetterValidity` and the character drawn for empty tiles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub colors: HashMap<LetterValidity, ThemeColor>,
+    pub placeholder: char,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            colors: HashMap::from([
+                (LetterValidity::Correct, ThemeColor::Ansi(10)),
+                (LetterValidity::Incorrect, ThemeColor::Ansi(15)),
+                (LetterValidity::WrongPos, ThemeColor::Ansi(11)),
+            ]),
+            placeholder: '·',
+        }
+    }
+}
+
+/// Split flat TOML or JSON into its key and value pairs, in order, without their quotes.
+/// Entries end at a newline, a `,` or a brace outside quotes.
+fn entries(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = Vec::new();
+    let mut key: Option<String> = None;
+    let mut token = String::new();
+    let mut end_entry = |key: &mut Option<String>, token: &mut String| -> Result<(), String> {
+        match key.take() {
+            Some(k) => entries.push((k, std::mem::take(token))),
+            None if token.is_empty() => {},
+            None => return Err(format!("Expected key = value: {}", token)),
+        }
+        Ok(())
+    };
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => token.extend(chars.next()),
+                    Some(c) => token.push(c),
+                    None => return Err(format!("Unterminated string: \"{}", token)),
+                }
+            },
+            '#' => {
+                chars.by_ref().find(|&c| c == '\n');
+                end_entry(&mut key, &mut token)?;
+            },
+            '=' | ':' if key.is_none() => key = Some(std::mem::take(&mut token)),
+            '\n' | ',' | '{' | '}' => end_entry(&mut key, &mut token)?,
+            c if c.is_whitespace() => {},
+            c => token.push(c),
+        }
+    }
+    end_entry(&mut key, &mut token)?;
+    Ok(entries)
+}
+
+impl Theme {
+    /// Load a theme from a file. See `Theme::parse` for the format.
+    pub fn load(path: &str) -> Result<Theme, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Cannot read theme file {}: {}", path, e))?;
+        Theme::parse(&text)
+    }
+
+    /// Write the theme as TOML that `Theme::parse` reads back.
+    pub fn to_toml(&self) -> String {
+        let mut text = String::new();
+        for (key, lv) in [("correct", LetterValidity::Correct), ("wrong_pos", LetterValidity::WrongPos), ("incorrect", LetterValidity::Incorrect)] {
+            if let Some(c) = self.colors.get(&lv) {
+                text.push_str(&format!("{} = \"{}\"\n", key, c.name()));
+            }
+        }
+        let placeholder = self.placeholder.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+        text.push_str(&format!("placeholder = \"{}\"\n", placeholder));
+        text
+    }
+
+    /// Parse a theme from flat TOML (`correct = "green"`) or a flat JSON object (`{"correct": "green"}`).
+    /// Keys are `correct`, `wrong_pos`, `incorrect` and `placeholder`; keys that are left out keep their default.
+    /// Only this flat subset is read: no tables or arrays, and strings only support `\"` and `\\` escapes.
+    /// Quoted text may contain `,`, `#`, `=` and `:`; outside quotes, `#` starts a comment running to the end of the line.
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let mut theme = Theme::default();
+        for (key, value) in entries(text)? {
+            let (key, value) = (key.as_str(), value.as_str());
+            let validity = match key {
+                "correct" => LetterValidity::Correct,
+                "wrong_pos" => LetterValidity::WrongPos,
+                "incorrect" => LetterValidity::Incorrect,
+                "placeholder" => {
+                    let mut chars = value.chars();
+                    theme.placeholder = match (chars.next(), chars.next()) {
+                        (Some(c), None) => c,
+                        _ => return Err(format!("Placeholder must be a single character: {}", value)),
+                    };
+                    continue;
+                },
+                _ => return Err(format!("Unknown theme key: {}", key)),
+            };
+            let color = ThemeColor::parse(value).map_err(|e| format!("{} for {}", e, key))?;
+            theme.colors.insert(validity, color);
+        }
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termion::color;
+
+    use crate::LetterValidity;
+
+    use super::{Theme, ThemeColor};
+
+    #[test]
+    fn theme_color1() {
+        assert_eq!(ThemeColor::parse("Light_Green"), Ok(ThemeColor::Ansi(10)));
+        assert_eq!(ThemeColor::parse("#ff8000"), Ok(ThemeColor::Rgb(255, 128, 0)));
+        assert!(ThemeColor::parse("chartreuse").is_err());
+        assert!(ThemeColor::parse("#ff80").is_err());
+        assert_eq!(ThemeColor::Ansi(10).fg(), color::Fg(color::LightGreen).to_string());
+    }
+
+    #[test]
+    fn theme_color_hex() {
+        assert_eq!(ThemeColor::Ansi(10).hex(), "#00ff00");
+        assert_eq!(ThemeColor::Ansi(196).hex(), "#ff0000");
+        assert_eq!(ThemeColor::Ansi(232).hex(), "#080808");
+        assert_eq!(ThemeColor::Rgb(255, 128, 0).hex(), "#ff8000");
+    }
+
+    #[test]
+    fn theme_parse1() {
+        let toml = Theme::parse("# Colors\ncorrect = \"blue\"\nplaceholder = \"_\"\n").unwrap();
+        let json = Theme::parse("{\"correct\": \"blue\", \"placeholder\": \"_\"}").unwrap();
+        assert_eq!(toml, json);
+        assert_eq!(toml.colors[&LetterValidity::Correct], ThemeColor::Ansi(4));
+        assert_eq!(toml.colors[&LetterValidity::WrongPos], ThemeColor::Ansi(11));
+        assert_eq!(toml.placeholder, '_');
+    }
+
+    #[test]
+    fn theme_to_toml1() {
+        let theme = Theme::parse("correct = \"#ff8000\"\nplaceholder = \"_\"").unwrap();
+        assert_eq!(theme.to_toml(), "correct = \"#ff8000\"\nwrong_pos = \"lightyellow\"\nincorrect = \"lightwhite\"\nplaceholder = \"_\"\n");
+        assert_eq!(Theme::parse(&theme.to_toml()), Ok(theme));
+    }
+
+    #[test]
+    fn theme_parse2() {
+        assert_eq!(Theme::parse("correct = \"chartreuse\""), Err(String::from("Unknown color: chartreuse for correct")));
+        assert!(Theme::parse("correct").is_err());
+        assert!(Theme::parse("missing = \"red\"").is_err());
+        assert!(Theme::parse("correct = \"red").is_err());
+    }
+
+    #[test]
+    fn theme_parse_quoted() {
+        let theme = Theme::parse("placeholder = \",\"\ncorrect = \"blue\" # primary, or \"red\"\n").unwrap();
+        assert_eq!(theme.placeholder, ',');
+        assert_eq!(theme.colors[&LetterValidity::Correct], ThemeColor::Ansi(4));
+        assert_eq!(Theme::parse("placeholder = \"#\"").unwrap().placeholder, '#');
+        assert_eq!(Theme::parse("{\"placeholder\": \":\", \"correct\": \"blue\"}").unwrap().placeholder, ':');
+        let quote = Theme::parse("placeholder = \"\\\"\"").unwrap();
+        assert_eq!(quote.placeholder, '"');
+        assert_eq!(Theme::parse(&quote.to_toml()), Ok(quote));
+        assert_eq!(Theme::parse(&theme.to_toml()), Ok(theme));
+    }
+}
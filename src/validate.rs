@@ -0,0 +1,122 @@
+//! Checks run over a word file before a game is created from it.
+use crate::tile_count;
+
+/// Problem found in a word list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The word list cannot be used
+    Error(String),
+
+    /// The word list can be used, but probably isn't what was intended
+    Warning(String),
+}
+
+/// A rule that a word list should follow.
+pub trait WordListValidator {
+    /// Check `words`, returning every problem found.
+    fn validate(&self, words: &[String]) -> Vec<Issue>;
+}
+
+/// Rejects a word list without any words.
+pub struct NotEmpty;
+
+impl WordListValidator for NotEmpty {
+    fn validate(&self, words: &[String]) -> Vec<Issue> {
+        if words.is_empty() {
+            vec![Issue::Error(String::from("Word file is empty"))]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Warns about words listed more than once.
+pub struct NoDuplicates;
+
+impl WordListValidator for NoDuplicates {
+    fn validate(&self, words: &[String]) -> Vec<Issue> {
+        let mut sorted: Vec<&String> = words.iter().collect();
+        sorted.sort_unstable();
+        let mut duplicates: Vec<&str> = sorted.windows(2).filter(|p| p[0] == p[1]).map(|p| p[0].as_str()).collect();
+        duplicates.dedup();
+        duplicates.into_iter().map(|w| Issue::Warning(format!("Duplicate word: {}", w))).collect()
+    }
+}
+
+/// Rejects words with fewer tiles than the given length.
+pub struct MinLength(pub usize);
+
+impl WordListValidator for MinLength {
+    fn validate(&self, words: &[String]) -> Vec<Issue> {
+        words.iter()
+            .filter(|w| tile_count(w) < self.0)
+            .map(|w| Issue::Error(format!("Word shorter than {} letters: {}", self.0, w)))
+            .collect()
+    }
+}
+
+/// Rejects words using letters outside the given alphabet.
+pub struct Alphabet(pub String);
+
+impl WordListValidator for Alphabet {
+    fn validate(&self, words: &[String]) -> Vec<Issue> {
+        words.iter()
+            .filter(|w| !w.chars().all(|c| self.0.contains(c)))
+            .map(|w| Issue::Error(format!("Word uses letters outside the alphabet: {}", w)))
+            .collect()
+    }
+}
+
+/// Rejects capitalized words, which are usually proper nouns.
+pub struct NoProperNouns;
+
+impl WordListValidator for NoProperNouns {
+    fn validate(&self, words: &[String]) -> Vec<Issue> {
+        words.iter()
+            .filter(|w| w.chars().next().is_some_and(char::is_uppercase))
+            .map(|w| Issue::Error(format!("Proper noun: {}", w)))
+            .collect()
+    }
+}
+
+/// Run every validator in `validators` over `words`, collecting the problems in order.
+pub fn run_validators(validators: &[Box<dyn WordListValidator>], words: &[String]) -> Vec<Issue> {
+    validators.iter().flat_map(|v| v.validate(words)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_validators, Alphabet, Issue, MinLength, NoDuplicates, NoProperNouns, NotEmpty, WordListValidator};
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn run_validators1() {
+        let validators: Vec<Box<dyn WordListValidator>> = vec![Box::new(MinLength(5)), Box::new(Alphabet(String::from("abcdefghijklmnopqrstuvwxyz")))];
+        let issues = run_validators(&validators, &words(&["apple", "fig", "grapé"]));
+        assert_eq!(issues, vec![
+            Issue::Error(String::from("Word shorter than 5 letters: fig")),
+            Issue::Error(String::from("Word uses letters outside the alphabet: grapé")),
+        ]);
+        assert!(run_validators(&validators, &words(&["apple", "grape"])).is_empty());
+    }
+
+    #[test]
+    fn not_empty1() {
+        assert_eq!(NotEmpty.validate(&[]), vec![Issue::Error(String::from("Word file is empty"))]);
+        assert!(NotEmpty.validate(&words(&["apple"])).is_empty());
+    }
+
+    #[test]
+    fn no_duplicates1() {
+        assert_eq!(NoDuplicates.validate(&words(&["apple", "grape", "apple", "apple"])),
+            vec![Issue::Warning(String::from("Duplicate word: apple"))]);
+    }
+
+    #[test]
+    fn no_proper_nouns1() {
+        assert_eq!(NoProperNouns.validate(&words(&["apple", "Paris"])), vec![Issue::Error(String::from("Proper noun: Paris"))]);
+    }
+}